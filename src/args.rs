@@ -1,17 +1,82 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::{num::ParseIntError, path::PathBuf, str::FromStr};
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct CmdArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub program: Option<ProgramArgs>,
+    #[command(flatten)]
+    pub run: RunArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the program in an interactive debugger.
+    Debug(ProgramArgs),
+    /// Serve the Debug Adapter Protocol over stdin and stdout.
+    Dap,
+    /// List the libfuncs and types of the program the emulator can't execute.
+    Check(CheckArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    pub program: PathBuf,
+    /// Print the report as JSON.
+    #[clap(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct ProgramArgs {
     pub program: PathBuf,
     pub entry_point: EntryPoint,
 
     pub args: Vec<String>,
     #[clap(long)]
     pub available_gas: Option<u64>,
+    /// Debug info of the program, used to show Cairo code locations.
+    #[clap(long)]
+    pub debug_info: Option<PathBuf>,
+    /// Seed of the random points picked by `ec_state_init`.
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+}
 
+#[derive(Debug, Args)]
+pub struct RunArgs {
     #[clap(long, short)]
     pub output: Option<PathBuf>,
+    #[clap(long, value_enum, default_value_t = TraceFormat::Json)]
+    pub trace_format: TraceFormat,
+
+    #[clap(long)]
+    pub profile: bool,
+    #[clap(long)]
+    pub profile_folded: Option<PathBuf>,
+    #[clap(long)]
+    pub gas_profile: Option<PathBuf>,
+    #[clap(long)]
+    pub gas_profile_folded: Option<PathBuf>,
+    #[clap(long)]
+    pub resources: Option<PathBuf>,
+    /// Write the per-function statement and branch coverage as JSON.
+    #[clap(long)]
+    pub coverage: Option<PathBuf>,
+    /// Write the Cairo line coverage in the LCOV format. Requires `--debug-info`.
+    #[clap(long)]
+    pub lcov: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum TraceFormat {
+    /// Full state dump of every statement.
+    Json,
+    /// Delta-encoded binary trace.
+    Compact,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,317 @@
+use crate::{
+    observer::{InvocationContext, StatementContext, VmObserver},
+    source_map::SourceMap,
+};
+use cairo_lang_sierra::{
+    extensions::ConcreteLibfunc,
+    ids::FunctionId,
+    program::{GenStatement, Program, StatementIdx},
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+/// Statement and branch coverage collector.
+///
+/// Register it as an observer on one or more [`VirtualMachine`](crate::VirtualMachine)s running
+/// the same program, or [`merge`](Coverage::merge) the coverage of separate runs.
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+    /// Number of times every statement has been executed.
+    pub statements: HashMap<StatementIdx, u64>,
+    /// Number of times every branch of a multi-branch invocation has been taken.
+    pub branches: HashMap<(StatementIdx, usize), u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FunctionCoverage {
+    pub function: String,
+    pub statements: usize,
+    pub covered_statements: usize,
+    /// Branches of the function's multi-branch invocations.
+    pub branches: usize,
+    pub covered_branches: usize,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (statement_idx, hits) in &other.statements {
+            *self.statements.entry(*statement_idx).or_default() += hits;
+        }
+        for (branch, hits) in &other.branches {
+            *self.branches.entry(*branch).or_default() += hits;
+        }
+    }
+
+    pub fn statement_hits(&self, statement_idx: StatementIdx) -> u64 {
+        self.statements.get(&statement_idx).copied().unwrap_or(0)
+    }
+
+    pub fn branch_hits(&self, statement_idx: StatementIdx, branch_idx: usize) -> u64 {
+        self.branches
+            .get(&(statement_idx, branch_idx))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Coverage of every function of the program, in declaration order.
+    pub fn report(&self, program: &Program) -> Vec<FunctionCoverage> {
+        function_ranges(program)
+            .into_iter()
+            .map(|(function_id, statements)| {
+                let mut coverage = FunctionCoverage {
+                    function: function_id.to_string(),
+                    statements: statements.len(),
+                    covered_statements: 0,
+                    branches: 0,
+                    covered_branches: 0,
+                };
+                for statement_idx in statements.map(StatementIdx) {
+                    if self.statement_hits(statement_idx) > 0 {
+                        coverage.covered_statements += 1;
+                    }
+                    for branch_idx in 0..branch_count(program, statement_idx) {
+                        coverage.branches += 1;
+                        if self.branch_hits(statement_idx, branch_idx) > 0 {
+                            coverage.covered_branches += 1;
+                        }
+                    }
+                }
+                coverage
+            })
+            .collect()
+    }
+
+    /// Render the Cairo line coverage in the LCOV format. Statements without a code location are
+    /// left out.
+    pub fn lcov(&self, program: &Program, source_map: &SourceMap) -> String {
+        #[derive(Default)]
+        struct FileCoverage {
+            functions: Vec<(String, usize, u64)>,
+            lines: BTreeMap<usize, u64>,
+            branches: Vec<(usize, StatementIdx, usize, u64)>,
+        }
+
+        let mut files = BTreeMap::<&str, FileCoverage>::new();
+        for (function_id, statements) in function_ranges(program) {
+            let entry_point = StatementIdx(statements.start);
+            if let Some(location) = source_map.location(entry_point) {
+                files.entry(&location.file).or_default().functions.push((
+                    function_id.to_string(),
+                    location.start.line + 1,
+                    self.statement_hits(entry_point),
+                ));
+            }
+
+            for statement_idx in statements.map(StatementIdx) {
+                let Some(location) = source_map.location(statement_idx) else {
+                    continue;
+                };
+                let file = files.entry(&location.file).or_default();
+                let line = location.start.line + 1;
+
+                // Several statements share a line; it's covered as many times as its most executed
+                // statement.
+                let hits = file.lines.entry(line).or_default();
+                *hits = (*hits).max(self.statement_hits(statement_idx));
+                for branch_idx in 0..branch_count(program, statement_idx) {
+                    file.branches.push((
+                        line,
+                        statement_idx,
+                        branch_idx,
+                        self.branch_hits(statement_idx, branch_idx),
+                    ));
+                }
+            }
+        }
+
+        let mut output = String::new();
+        for (path, file) in files {
+            writeln!(output, "TN:").unwrap();
+            writeln!(output, "SF:{path}").unwrap();
+            for (name, line, _) in &file.functions {
+                writeln!(output, "FN:{line},{name}").unwrap();
+            }
+            for (name, _, hits) in &file.functions {
+                writeln!(output, "FNDA:{hits},{name}").unwrap();
+            }
+            writeln!(output, "FNF:{}", file.functions.len()).unwrap();
+            let hit_functions = file.functions.iter().filter(|(_, _, hits)| *hits > 0);
+            writeln!(output, "FNH:{}", hit_functions.count()).unwrap();
+
+            for (line, statement_idx, branch_idx, hits) in &file.branches {
+                match hits {
+                    0 => writeln!(output, "BRDA:{line},{},{branch_idx},-", statement_idx.0),
+                    _ => writeln!(
+                        output,
+                        "BRDA:{line},{},{branch_idx},{hits}",
+                        statement_idx.0
+                    ),
+                }
+                .unwrap();
+            }
+            writeln!(output, "BRF:{}", file.branches.len()).unwrap();
+            let hit_branches = file.branches.iter().filter(|(.., hits)| *hits > 0);
+            writeln!(output, "BRH:{}", hit_branches.count()).unwrap();
+
+            for (line, hits) in &file.lines {
+                writeln!(output, "DA:{line},{hits}").unwrap();
+            }
+            writeln!(output, "LF:{}", file.lines.len()).unwrap();
+            let hit_lines = file.lines.values().filter(|hits| **hits > 0);
+            writeln!(output, "LH:{}", hit_lines.count()).unwrap();
+            writeln!(output, "end_of_record").unwrap();
+        }
+
+        output
+    }
+}
+
+impl VmObserver for Coverage {
+    fn before_statement(&mut self, statement: &StatementContext) {
+        *self.statements.entry(statement.statement_idx).or_default() += 1;
+    }
+
+    fn after_invocation(&mut self, invocation: &InvocationContext) {
+        if invocation.libfunc.branch_signatures().len() > 1 {
+            *self
+                .branches
+                .entry((invocation.statement_idx, invocation.branch_idx))
+                .or_default() += 1;
+        }
+    }
+}
+
+/// The statements of every function. Functions are laid out contiguously, each one starting at
+/// its entry point.
+fn function_ranges(program: &Program) -> Vec<(&FunctionId, std::ops::Range<usize>)> {
+    let mut entry_points = program
+        .funcs
+        .iter()
+        .map(|function| function.entry_point.0)
+        .collect::<Vec<_>>();
+    entry_points.sort_unstable();
+
+    program
+        .funcs
+        .iter()
+        .map(|function| {
+            let start = function.entry_point.0;
+            let end = entry_points
+                .iter()
+                .copied()
+                .find(|entry_point| *entry_point > start)
+                .unwrap_or(program.statements.len());
+            (&function.id, start..end)
+        })
+        .collect()
+}
+
+/// Number of branches of a statement, if it has more than one.
+fn branch_count(program: &Program, statement_idx: StatementIdx) -> usize {
+    match &program.statements[statement_idx.0] {
+        GenStatement::Invocation(invocation) if invocation.branches.len() > 1 => {
+            invocation.branches.len()
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        source_map::{SourceLocation, SourcePosition},
+        starknet::StubSyscallHandler,
+        Value, VirtualMachine,
+    };
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_coverage() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type NonZeroFelt252 = NonZero<felt252>;
+
+                    libfunc felt252_is_zero = felt252_is_zero;
+                    libfunc branch_align = branch_align;
+                    libfunc drop<NonZeroFelt252> = drop<NonZeroFelt252>;
+                    libfunc felt252_const<0> = felt252_const<0>;
+                    libfunc felt252_const<1> = felt252_const<1>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    felt252_is_zero([0]) { fallthrough() 5([1]) };
+                    branch_align() -> ();
+                    felt252_const<0>() -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+                    branch_align() -> ();
+                    drop<NonZeroFelt252>([1]) -> ();
+                    felt252_const<1>() -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    is_nonzero@0([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+        let coverage = Arc::new(Mutex::new(Coverage::new()));
+
+        let run = |arg: u64| {
+            let mut vm = VirtualMachine::new(program.clone());
+            vm.add_observer(coverage.clone());
+            vm.call_program(&program.funcs[0], 0, [Value::Felt(arg.into())]);
+            vm.run_with_trace(&mut StubSyscallHandler::default());
+        };
+
+        run(0);
+        let report = coverage.lock().unwrap().report(&program);
+        assert_eq!(
+            report,
+            [FunctionCoverage {
+                function: "is_nonzero".to_string(),
+                statements: 10,
+                covered_statements: 5,
+                branches: 2,
+                covered_branches: 1,
+            }]
+        );
+
+        run(7);
+        run(8);
+        let coverage = coverage.lock().unwrap();
+        let report = coverage.report(&program);
+        assert_eq!(report[0].covered_statements, 10);
+        assert_eq!(report[0].covered_branches, 2);
+        assert_eq!(coverage.branch_hits(StatementIdx(0), 1), 2);
+
+        let mut source_map = SourceMap::new();
+        for (statement_idx, line) in [(0, 1), (1, 2), (2, 2), (5, 3)] {
+            source_map.insert(
+                StatementIdx(statement_idx),
+                SourceLocation {
+                    file: "lib.cairo".to_string(),
+                    start: SourcePosition { line, col: 4 },
+                    end: SourcePosition { line, col: 8 },
+                    in_macro: false,
+                },
+            );
+        }
+        assert_eq!(
+            coverage.lcov(&program, &source_map),
+            "TN:\nSF:lib.cairo\nFN:2,is_nonzero\nFNDA:3,is_nonzero\nFNF:1\nFNH:1\n\
+             BRDA:2,0,0,1\nBRDA:2,0,1,2\nBRF:2\nBRH:2\n\
+             DA:2,3\nDA:3,1\nDA:4,2\nLF:3\nLH:3\nend_of_record\n"
+        );
+    }
+}
@@ -0,0 +1,711 @@
+use crate::{
+    debugger::{function_matches, render_value, Breakpoint, Debugger, StopReason},
+    output::OutputSink,
+    source_map::SourceMap,
+    starknet::StubSyscallHandler,
+    Value, VirtualMachine,
+};
+use cairo_lang_sierra::{debug_info::DebugInfo, program::StatementIdx, ProgramParser};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use starknet_types_core::felt::Felt;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The only thread reported to the client.
+const THREAD_ID: u64 = 1;
+
+/// Serve a Debug Adapter Protocol session over a pair of streams, usually stdin and stdout.
+///
+/// Returns once the client disconnects or closes the input.
+pub fn serve(mut input: impl BufRead, output: impl Write) -> io::Result<()> {
+    let mut session = Session::new(output);
+    while let Some(request) = read_message(&mut input)? {
+        if !session.handle(request)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    seq: u64,
+    command: String,
+    #[serde(default)]
+    arguments: JsonValue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LaunchArguments {
+    program: PathBuf,
+    /// Function to run, by debug name or id. Defaults to the first one.
+    entry_point: Option<String>,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Gas given to the entry point, which first pays for its own cost out of it. Defaults to as
+    /// much as the gas counter can hold.
+    available_gas: Option<u64>,
+    debug_info: Option<PathBuf>,
+    #[serde(default)]
+    stop_on_entry: bool,
+}
+
+struct Session<W> {
+    output: W,
+    seq: u64,
+    debugger: Option<Debugger>,
+    syscall_handler: StubSyscallHandler,
+    program_path: PathBuf,
+    /// Line of every statement in the Sierra file (1-based), if it could be found.
+    statement_lines: Vec<Option<usize>>,
+    stop_on_entry: bool,
+    /// Lines with a breakpoint in each file. They can be set before the program is launched.
+    source_breakpoints: HashMap<PathBuf, Vec<usize>>,
+    function_breakpoints: Vec<String>,
+}
+
+impl<W: Write> Session<W> {
+    fn new(output: W) -> Self {
+        Self {
+            output,
+            seq: 0,
+            debugger: None,
+            syscall_handler: StubSyscallHandler::default(),
+            program_path: PathBuf::new(),
+            statement_lines: Vec::new(),
+            stop_on_entry: false,
+            source_breakpoints: HashMap::new(),
+            function_breakpoints: Vec::new(),
+        }
+    }
+
+    /// Handle a request. Returns `false` when the session should end.
+    fn handle(&mut self, request: Request) -> io::Result<bool> {
+        let result = match request.command.as_str() {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsFunctionBreakpoints": true,
+                "supportsEvaluateForHovers": true,
+            })),
+            "launch" | "attach" => self.launch(&request.arguments),
+            "setBreakpoints" => self.set_breakpoints(&request.arguments),
+            "setFunctionBreakpoints" => self.set_function_breakpoints(&request.arguments),
+            "disconnect" | "terminate" => {
+                self.respond(&request, Ok(JsonValue::Null))?;
+                return Ok(false);
+            }
+            _ => self.handle_running(&request),
+        };
+
+        self.respond(&request, result)?;
+        match request.command.as_str() {
+            // The client sends the breakpoints once it gets this, which may be before the launch.
+            "initialize" => self.send_event("initialized", JsonValue::Null)?,
+            "configurationDone" if self.stop_on_entry => self.send_event(
+                "stopped",
+                json!({ "reason": "entry", "threadId": THREAD_ID }),
+            )?,
+            "configurationDone" => self.run(Debugger::resume)?,
+            "continue" => self.run(Debugger::resume)?,
+            "next" => self.run(Debugger::next)?,
+            "stepIn" => self.run(Debugger::step)?,
+            "stepOut" => self.run(Debugger::finish)?,
+            _ => {}
+        }
+
+        Ok(true)
+    }
+
+    /// Handle the requests that need a launched program.
+    fn handle_running(&mut self, request: &Request) -> Result<JsonValue, String> {
+        let Some(debugger) = &self.debugger else {
+            return Err("no program has been launched".to_string());
+        };
+
+        Ok(match request.command.as_str() {
+            "threads" => json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            "stackTrace" => {
+                let frames = debugger
+                    .backtrace()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(depth, frame)| {
+                        let (path, line, column) = match &frame.location {
+                            Some(location) => (
+                                PathBuf::from(&location.file),
+                                location.start.line + 1,
+                                location.start.col + 1,
+                            ),
+                            None => (
+                                self.program_path.clone(),
+                                self.statement_lines[frame.statement_idx.0].unwrap_or(0),
+                                1,
+                            ),
+                        };
+                        json!({
+                            "id": depth,
+                            "name": frame.function_id.to_string(),
+                            "source": { "path": path },
+                            "line": line,
+                            "column": column,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "stackFrames": frames, "totalFrames": frames.len() })
+            }
+            "scopes" => {
+                let depth = request.arguments["frameId"].as_u64().unwrap_or(0);
+                json!({ "scopes": [{
+                    "name": "Locals",
+                    "variablesReference": depth + 1,
+                    "expensive": false,
+                }] })
+            }
+            "variables" => {
+                let depth = request.arguments["variablesReference"]
+                    .as_u64()
+                    .unwrap_or(0)
+                    .saturating_sub(1);
+                let variables = debugger
+                    .frame_variables(depth as usize)
+                    .into_iter()
+                    .map(|(id, ty, value)| {
+                        let name = match id.debug_name {
+                            Some(name) => name.to_string(),
+                            None => format!("[{}]", id.id),
+                        };
+                        let value = match &ty {
+                            Some(ty) => render_value(debugger.vm.registry(), ty, &value),
+                            None => format!("{value:?}"),
+                        };
+                        json!({
+                            "name": name,
+                            "value": value,
+                            "type": ty.map(|ty| ty.to_string()),
+                            "variablesReference": 0,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({ "variables": variables })
+            }
+            "evaluate" => {
+                let expression = request.arguments["expression"].as_str().unwrap_or_default();
+                let result = debugger
+                    .print(expression.trim())
+                    .ok_or_else(|| format!("no variable `{expression}` in the current frame"))?;
+                json!({ "result": result, "variablesReference": 0 })
+            }
+            "continue" => json!({ "allThreadsContinued": true }),
+            "configurationDone" | "next" | "stepIn" | "stepOut" => JsonValue::Null,
+            command => return Err(format!("unsupported command `{command}`")),
+        })
+    }
+
+    fn launch(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let arguments = LaunchArguments::deserialize(arguments).map_err(|e| e.to_string())?;
+
+        let source_code = fs::read_to_string(&arguments.program).map_err(|e| e.to_string())?;
+        let mut program = ProgramParser::new()
+            .parse(&source_code)
+            .map_err(|e| e.to_string())?;
+        // The lines are matched against the statements as written, before renaming any id.
+        self.statement_lines = statement_lines(&source_code, &program);
+
+        let mut source_map = None;
+        if let Some(path) = &arguments.debug_info {
+            let debug_info: DebugInfo =
+                serde_json::from_reader(File::open(path).map_err(|e| e.to_string())?)
+                    .map_err(|e| e.to_string())?;
+            debug_info.populate(&mut program);
+            source_map = Some(SourceMap::from_debug_info(&debug_info).map_err(|e| e.to_string())?);
+        }
+
+        let function = match &arguments.entry_point {
+            Some(name) => program
+                .funcs
+                .iter()
+                .find(|function| function_matches(&function.id, name)),
+            None => program.funcs.first(),
+        }
+        .ok_or("entry point not found")?
+        .clone();
+        let args = arguments
+            .args
+            .iter()
+            .map(|arg| {
+                match arg.strip_prefix("0x") {
+                    Some(hex) => Felt::from_hex(hex),
+                    None => Felt::from_dec_str(arg),
+                }
+                .map(Value::Felt)
+                .map_err(|_| format!("invalid felt252 argument `{arg}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut vm = VirtualMachine::new(Arc::new(program));
+        let initial_gas = vm
+            .gas
+            .get_initial_available_gas(
+                &function.id,
+                Some(arguments.available_gas.unwrap_or(u64::MAX)),
+            )
+            .map_err(|e| e.to_string())?;
+        // Stdout is reserved for the protocol messages, so the printed text is sent as events.
+        vm.set_output_sink(OutputSink::Capture);
+        if let Some(source_map) = source_map {
+            vm.set_source_map(source_map);
+        }
+        vm.call_program(&function, initial_gas, args);
+
+        self.debugger = Some(Debugger::new(vm));
+        self.program_path = arguments.program;
+        self.stop_on_entry = arguments.stop_on_entry;
+        self.update_breakpoints();
+
+        Ok(JsonValue::Null)
+    }
+
+    fn set_breakpoints(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        let path = PathBuf::from(arguments["source"]["path"].as_str().unwrap_or_default());
+        let lines = arguments["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|breakpoint| breakpoint["line"].as_u64())
+            .map(|line| line as usize)
+            .collect::<Vec<_>>();
+
+        let breakpoints = lines
+            .iter()
+            .map(|&line| {
+                let verified = !self.line_statements(&path, line).is_empty();
+                json!({ "verified": verified, "line": line })
+            })
+            .collect::<Vec<_>>();
+
+        self.source_breakpoints.insert(path, lines);
+        self.update_breakpoints();
+
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    /// The statements a breakpoint on a line stops at, once the program has been launched.
+    fn line_statements(&self, path: &Path, line: usize) -> Vec<StatementIdx> {
+        let Some(debugger) = &self.debugger else {
+            return Vec::new();
+        };
+
+        if same_file(path, &self.program_path) {
+            self.statement_lines
+                .iter()
+                .position(|statement_line| *statement_line == Some(line))
+                .map(|idx| vec![StatementIdx(idx)])
+                .unwrap_or_default()
+        } else {
+            debugger
+                .vm
+                .source_map()
+                .map(|source_map| source_statements(source_map, &debugger.vm, path, line))
+                .unwrap_or_default()
+        }
+    }
+
+    fn set_function_breakpoints(&mut self, arguments: &JsonValue) -> Result<JsonValue, String> {
+        self.function_breakpoints = arguments["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|breakpoint| breakpoint["name"].as_str())
+            .map(str::to_string)
+            .collect();
+        self.update_breakpoints();
+
+        let breakpoints = self
+            .function_breakpoints
+            .iter()
+            .map(|_| json!({ "verified": true }))
+            .collect::<Vec<_>>();
+        Ok(json!({ "breakpoints": breakpoints }))
+    }
+
+    fn update_breakpoints(&mut self) {
+        let statements = self
+            .source_breakpoints
+            .iter()
+            .flat_map(|(path, lines)| {
+                lines
+                    .iter()
+                    .flat_map(|&line| self.line_statements(path, line))
+            })
+            .collect::<Vec<_>>();
+        let Some(debugger) = &mut self.debugger else {
+            return;
+        };
+
+        debugger.clear_breakpoints();
+        for statement_idx in statements {
+            debugger.add_breakpoint(Breakpoint::Statement(statement_idx));
+        }
+        for name in &self.function_breakpoints {
+            debugger.add_breakpoint(Breakpoint::Function(name.clone()));
+        }
+    }
+
+    /// Run the debugger and report why it stopped.
+    fn run(
+        &mut self,
+        action: fn(&mut Debugger, &mut StubSyscallHandler) -> StopReason,
+    ) -> io::Result<()> {
+        let Some(debugger) = &mut self.debugger else {
+            return Ok(());
+        };
+
+        let stop_reason = action(debugger, &mut self.syscall_handler);
+        for output in debugger.vm.take_output() {
+            self.send_event(
+                "output",
+                json!({ "category": "stdout", "output": output.text }),
+            )?;
+        }
+
+        let Some(debugger) = &mut self.debugger else {
+            return Ok(());
+        };
+        match stop_reason {
+            StopReason::Step => self.send_event(
+                "stopped",
+                json!({ "reason": "step", "threadId": THREAD_ID }),
+            ),
+            StopReason::Watchpoint(_) => self.send_event(
+                "stopped",
+                json!({ "reason": "data breakpoint", "threadId": THREAD_ID }),
+            ),
+            StopReason::Breakpoint(_) => self.send_event(
+                "stopped",
+                json!({ "reason": "breakpoint", "threadId": THREAD_ID }),
+            ),
+            StopReason::Finished => {
+                let output = match debugger.return_values() {
+                    Some(values) => format!("Program finished, returning {values:?}.\n"),
+                    None => "Program finished.\n".to_string(),
+                };
+                self.send_event("output", json!({ "category": "console", "output": output }))?;
+                self.send_event("exited", json!({ "exitCode": 0 }))?;
+                self.send_event("terminated", JsonValue::Null)
+            }
+        }
+    }
+
+    fn respond(&mut self, request: &Request, result: Result<JsonValue, String>) -> io::Result<()> {
+        let mut message = json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(JsonValue::Null) => {}
+            Ok(body) => message["body"] = body,
+            Err(error) => message["message"] = error.into(),
+        }
+        self.send(message)
+    }
+
+    fn send_event(&mut self, event: &str, body: JsonValue) -> io::Result<()> {
+        let mut message = json!({ "type": "event", "event": event });
+        if !body.is_null() {
+            message["body"] = body;
+        }
+        self.send(message)
+    }
+
+    fn send(&mut self, mut message: JsonValue) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = self.seq.into();
+
+        let content = serde_json::to_vec(&message)?;
+        write!(self.output, "Content-Length: {}\r\n\r\n", content.len())?;
+        self.output.write_all(&content)?;
+        self.output.flush()
+    }
+}
+
+/// Read a message framed by a `Content-Length` header. Returns `None` at the end of the input.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Request>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?,
+                );
+            }
+        }
+    }
+
+    let mut content = vec![0; content_length.unwrap()];
+    input.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+/// Find the line of every statement by matching the Sierra source against the parsed program.
+fn statement_lines(
+    source_code: &str,
+    program: &cairo_lang_sierra::program::Program,
+) -> Vec<Option<usize>> {
+    let normalize = |text: &str| {
+        text.chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+
+    let mut lines = source_code
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, normalize(line.trim().trim_end_matches(';'))));
+    program
+        .statements
+        .iter()
+        .map(|statement| {
+            let statement = normalize(&statement.to_string());
+            // Statements missing from the source don't prevent finding the following ones.
+            let mut search = lines.clone();
+            let (line, _) = search.find(|(_, line)| *line == statement)?;
+            lines = search;
+            Some(line)
+        })
+        .collect()
+}
+
+/// Statements that start the code of a line in a Cairo source file.
+fn source_statements(
+    source_map: &SourceMap,
+    vm: &VirtualMachine,
+    path: &Path,
+    line: usize,
+) -> Vec<StatementIdx> {
+    let is_line = |statement_idx: usize| {
+        source_map
+            .location(StatementIdx(statement_idx))
+            .is_some_and(|location| {
+                location.start.line + 1 == line && same_file(path, Path::new(&location.file))
+            })
+    };
+
+    (0..vm.program.statements.len())
+        .filter(|&idx| is_line(idx) && (idx == 0 || !is_line(idx - 1)))
+        .map(StatementIdx)
+        .collect()
+}
+
+/// Paths sent by the client are usually absolute, while the ones in the debug info may not be.
+fn same_file(lhs: &Path, rhs: &Path) -> bool {
+    lhs.ends_with(rhs) || rhs.ends_with(lhs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_breakpoint_session() {
+        let program = double_program();
+
+        let requests = [
+            json!({ "command": "initialize" }),
+            json!({ "command": "launch", "arguments": {
+                "program": program.path(),
+                "entryPoint": "main",
+                "args": ["21"],
+            } }),
+            json!({ "command": "setBreakpoints", "arguments": {
+                "source": { "path": program.path() },
+                "breakpoints": [{ "line": 13 }],
+            } }),
+            json!({ "command": "setFunctionBreakpoints", "arguments": {
+                "breakpoints": [{ "name": "double" }],
+            } }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "stackTrace", "arguments": { "threadId": THREAD_ID } }),
+            json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+            json!({ "command": "continue", "arguments": { "threadId": THREAD_ID } }),
+            json!({ "command": "continue", "arguments": { "threadId": THREAD_ID } }),
+            json!({ "command": "disconnect" }),
+        ];
+        let messages = run_session(requests);
+        assert!(messages
+            .iter()
+            .filter(|message| message["type"] == "response")
+            .all(|message| message["success"] == true));
+
+        let events = messages
+            .iter()
+            .filter(|message| message["type"] == "event")
+            .map(|message| {
+                let reason = message["body"]["reason"].as_str().unwrap_or_default();
+                format!("{}{reason}", message["event"].as_str().unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(messages[0]["command"], "initialize");
+        assert_eq!(messages[1]["event"], "initialized");
+        assert_eq!(
+            events,
+            [
+                "initialized",
+                "stoppedbreakpoint",
+                "stoppedbreakpoint",
+                "output",
+                "exited",
+                "terminated"
+            ]
+        );
+
+        let response = |command: &str| {
+            messages
+                .iter()
+                .find(|message| message["command"] == command)
+                .unwrap()
+        };
+        let breakpoints = &response("setBreakpoints")["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["verified"], true);
+
+        let stack_frames = &response("stackTrace")["body"]["stackFrames"];
+        assert_eq!(stack_frames[0]["name"], "double");
+        assert_eq!(stack_frames[0]["line"], 12);
+        assert_eq!(stack_frames[1]["name"], "main");
+        assert_eq!(stack_frames[1]["line"], 9);
+
+        let variables = &response("variables")["body"]["variables"];
+        assert_eq!(variables[0]["name"], "[0]");
+        assert_eq!(variables[0]["value"], "21");
+        assert_eq!(variables[0]["type"], "felt252");
+    }
+
+    #[test]
+    fn test_breakpoints_before_launch() {
+        let program = double_program();
+
+        let requests = [
+            json!({ "command": "initialize" }),
+            json!({ "command": "setBreakpoints", "arguments": {
+                "source": { "path": program.path() },
+                "breakpoints": [{ "line": 13 }],
+            } }),
+            json!({ "command": "launch", "arguments": {
+                "program": program.path(),
+                "args": ["21"],
+            } }),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "stackTrace", "arguments": { "threadId": THREAD_ID } }),
+            json!({ "command": "disconnect" }),
+        ];
+        let messages = run_session(requests);
+
+        assert!(messages
+            .iter()
+            .filter(|message| message["type"] == "response")
+            .all(|message| message["success"] == true));
+        let stopped = messages
+            .iter()
+            .find(|message| message["event"] == "stopped")
+            .unwrap();
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+        let stack_trace = messages
+            .iter()
+            .find(|message| message["command"] == "stackTrace")
+            .unwrap();
+        let stack_frames = &stack_trace["body"]["stackFrames"];
+        assert_eq!(stack_frames[0]["name"], "double");
+        assert_eq!(stack_frames[0]["line"], 13);
+    }
+
+    fn double_program() -> tempfile::NamedTempFile {
+        let mut program = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            program,
+            r#"
+                type felt252 = felt252;
+
+                libfunc felt252_add = felt252_add;
+                libfunc dup<felt252> = dup<felt252>;
+                libfunc store_temp<felt252> = store_temp<felt252>;
+                libfunc function_call<user@double> = function_call<user@double>;
+
+                function_call<user@double>([0]) -> ([1]);
+                store_temp<felt252>([1]) -> ([1]);
+                return([1]);
+                dup<felt252>([0]) -> ([0], [1]);
+                felt252_add([0], [1]) -> ([2]);
+                store_temp<felt252>([2]) -> ([2]);
+                return([2]);
+
+                main@0([0]: felt252) -> (felt252);
+                double@3([0]: felt252) -> (felt252);
+            "#
+        )
+        .unwrap();
+
+        program
+    }
+
+    /// Send the requests to a new session and return every message it sent back.
+    fn run_session(requests: impl IntoIterator<Item = JsonValue>) -> Vec<JsonValue> {
+        let mut input = Vec::new();
+        for (seq, mut request) in requests.into_iter().enumerate() {
+            request["seq"] = seq.into();
+            request["type"] = "request".into();
+            let content = request.to_string();
+            write!(input, "Content-Length: {}\r\n\r\n{content}", content.len()).unwrap();
+        }
+
+        let mut output = Vec::new();
+        serve(input.as_slice(), &mut output).unwrap();
+
+        let mut output = BufReader::new(output.as_slice());
+        let mut messages = Vec::new();
+        while let Some(message) = read_message_value(&mut output) {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn read_message_value(input: &mut impl BufRead) -> Option<JsonValue> {
+        let mut header = String::new();
+        input.read_line(&mut header).unwrap();
+        let length = header
+            .trim()
+            .strip_prefix("Content-Length: ")?
+            .parse()
+            .unwrap();
+        input.read_line(&mut String::new()).unwrap();
+
+        let mut content = vec![0; length];
+        input.read_exact(&mut content).unwrap();
+        serde_json::from_slice(&content).ok()
+    }
+}
@@ -0,0 +1,463 @@
+use crate::{
+    debug::libfunc_to_name, find_real_type, source_map::SourceLocation,
+    starknet::StarknetSyscallHandler, vm::SierraFrame, Value, VirtualMachine,
+};
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        ConcreteLibfunc,
+    },
+    ids::{ConcreteTypeId, FunctionId, VarId},
+    program::{GenStatement, StatementIdx},
+    program_registry::ProgramRegistry,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+/// Drives a [`VirtualMachine`] one statement at a time, stopping at breakpoints.
+#[derive(Debug)]
+pub struct Debugger {
+    pub vm: VirtualMachine,
+    breakpoints: Vec<Breakpoint>,
+    /// Values returned by the entry point, once it has finished.
+    return_values: Option<Vec<Value>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    Statement(StatementIdx),
+    /// Stop when entering a function, matched by its full debug name, the last segment of its
+    /// path or its numeric id.
+    Function(String),
+    /// Stop before invoking a libfunc, matched by its generic name (`felt252_add`) or its
+    /// concrete debug name (`store_temp<felt252>`).
+    Libfunc(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// The requested step has been completed.
+    Step,
+    /// The breakpoint with the given index has been hit.
+    Breakpoint(usize),
+    /// The VM watchpoint with the given index has been hit by the last statement.
+    Watchpoint(usize),
+    /// The entry point has returned.
+    Finished,
+}
+
+/// A frame of the call stack, as seen by the debugger.
+#[derive(Clone, Debug)]
+pub struct FrameInfo {
+    pub function_id: FunctionId,
+    pub statement_idx: StatementIdx,
+    pub location: Option<SourceLocation>,
+}
+
+impl Debugger {
+    pub fn new(vm: VirtualMachine) -> Self {
+        Self {
+            vm,
+            breakpoints: Vec::new(),
+            return_values: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(breakpoint);
+        self.breakpoints.len() - 1
+    }
+
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        (index < self.breakpoints.len()).then(|| self.breakpoints.remove(index))
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.vm.frames().is_empty()
+    }
+
+    pub fn return_values(&self) -> Option<&[Value]> {
+        self.return_values.as_deref()
+    }
+
+    /// The statement that will be executed next.
+    pub fn current_statement(&self) -> Option<StatementIdx> {
+        self.vm.frames().last().map(|frame| frame.pc)
+    }
+
+    /// Execute a single statement, entering function calls.
+    pub fn step(&mut self, syscall_handler: &mut impl StarknetSyscallHandler) -> StopReason {
+        let Some((statement_idx, state)) = self.vm.step(syscall_handler) else {
+            return StopReason::Finished;
+        };
+
+        if self.is_finished() {
+            if let GenStatement::Return(ids) = &self.vm.program.statements[statement_idx.0] {
+                self.return_values = Some(ids.iter().map(|id| state[id].clone()).collect());
+            }
+            return StopReason::Finished;
+        }
+        if let Some(hit) = self.vm.take_watch_hit() {
+            return StopReason::Watchpoint(hit.index);
+        }
+
+        StopReason::Step
+    }
+
+    /// Undo the last executed statement. Returns `false` when there is nothing to undo, which is
+    /// always the case unless the VM history has been enabled.
+    pub fn step_back(&mut self, syscall_handler: &mut impl StarknetSyscallHandler) -> bool {
+        if self.vm.step_back(syscall_handler).is_none() {
+            return false;
+        }
+
+        self.return_values = None;
+        true
+    }
+
+    /// Execute a single statement, running function calls until they return.
+    pub fn next(&mut self, syscall_handler: &mut impl StarknetSyscallHandler) -> StopReason {
+        let depth = self.vm.frames().len();
+        self.run_while(syscall_handler, |debugger| {
+            debugger.vm.frames().len() > depth
+        })
+    }
+
+    /// Run until the current function returns.
+    pub fn finish(&mut self, syscall_handler: &mut impl StarknetSyscallHandler) -> StopReason {
+        let depth = self.vm.frames().len();
+        self.run_while(syscall_handler, |debugger| {
+            debugger.vm.frames().len() >= depth
+        })
+    }
+
+    /// Run until a breakpoint is hit or the entry point returns.
+    pub fn resume(&mut self, syscall_handler: &mut impl StarknetSyscallHandler) -> StopReason {
+        self.run_while(syscall_handler, |_| true)
+    }
+
+    /// Step at least once, then keep stepping while `condition` holds and no breakpoint is hit.
+    fn run_while(
+        &mut self,
+        syscall_handler: &mut impl StarknetSyscallHandler,
+        condition: impl Fn(&Self) -> bool,
+    ) -> StopReason {
+        loop {
+            match self.step(syscall_handler) {
+                reason @ (StopReason::Finished | StopReason::Watchpoint(_)) => return reason,
+                _ => {
+                    if let Some(index) = self.hit_breakpoint() {
+                        return StopReason::Breakpoint(index);
+                    }
+                    if !condition(self) {
+                        return StopReason::Step;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Index of the first breakpoint matching the statement about to be executed.
+    pub fn hit_breakpoint(&self) -> Option<usize> {
+        let frame = self.vm.frames().last()?;
+        let registry = self.vm.registry();
+
+        self.breakpoints
+            .iter()
+            .position(|breakpoint| match breakpoint {
+                Breakpoint::Statement(statement_idx) => frame.pc == *statement_idx,
+                Breakpoint::Function(name) => {
+                    let function = registry.get_function(&frame.function_id).unwrap();
+                    frame.pc == function.entry_point && function_matches(&frame.function_id, name)
+                }
+                Breakpoint::Libfunc(name) => match &self.vm.program.statements[frame.pc.0] {
+                    GenStatement::Invocation(invocation) => {
+                        invocation.libfunc_id.debug_name.as_deref() == Some(name.as_str())
+                            || libfunc_to_name(
+                                registry.get_libfunc(&invocation.libfunc_id).unwrap(),
+                            ) == name
+                    }
+                    GenStatement::Return(_) => false,
+                },
+            })
+    }
+
+    /// The call stack, innermost frame first.
+    pub fn backtrace(&self) -> Vec<FrameInfo> {
+        self.vm
+            .frames()
+            .iter()
+            .rev()
+            .map(|frame| FrameInfo {
+                function_id: frame.function_id.clone(),
+                statement_idx: frame.pc,
+                location: self
+                    .vm
+                    .source_map()
+                    .and_then(|map| map.location(frame.pc))
+                    .cloned(),
+            })
+            .collect()
+    }
+
+    /// The variables of the current frame along with their types, sorted by id.
+    pub fn variables(&self) -> Vec<(VarId, Option<ConcreteTypeId>, Value)> {
+        self.frame_variables(0)
+    }
+
+    /// The variables of a frame along with their types, sorted by id. Frames are counted from the
+    /// innermost one, like in [`Debugger::backtrace`].
+    pub fn frame_variables(&self, depth: usize) -> Vec<(VarId, Option<ConcreteTypeId>, Value)> {
+        let Some(frame) = self.vm.frames().iter().rev().nth(depth) else {
+            return Vec::new();
+        };
+        let types = self.variable_types(frame);
+
+        let mut variables = frame
+            .state
+            .iter()
+            .map(|(id, value)| (id.clone(), types.get(id).cloned(), value.clone()))
+            .collect::<Vec<_>>();
+        variables.sort_by_key(|(id, _, _)| id.id);
+        variables
+    }
+
+    /// Render a variable of the current frame, found by its debug name or id (`3` or `[3]`).
+    pub fn print(&self, name: &str) -> Option<String> {
+        let id = name.trim_start_matches('[').trim_end_matches(']');
+        let (_, ty, value) = self.variables().into_iter().find(|(var_id, _, _)| {
+            var_id.debug_name.as_deref() == Some(name) || var_id.id.to_string() == id
+        })?;
+
+        Some(match ty {
+            Some(ty) => format!("{ty} = {}", render_value(self.vm.registry(), &ty, &value)),
+            None => format!("{value:?}"),
+        })
+    }
+
+    /// Types of the variables alive at the frame's statement, found by walking its function from
+    /// the entry point.
+    fn variable_types(&self, frame: &SierraFrame) -> HashMap<VarId, ConcreteTypeId> {
+        let registry = self.vm.registry();
+        let function = registry.get_function(&frame.function_id).unwrap();
+
+        let mut visited = HashSet::new();
+        let mut pending = vec![(
+            function.entry_point,
+            function
+                .params
+                .iter()
+                .map(|param| (param.id.clone(), param.ty.clone()))
+                .collect::<HashMap<_, _>>(),
+        )];
+        while let Some((statement_idx, mut types)) = pending.pop() {
+            if statement_idx == frame.pc {
+                return types;
+            }
+            if !visited.insert(statement_idx) {
+                continue;
+            }
+
+            if let GenStatement::Invocation(invocation) =
+                &self.vm.program.statements[statement_idx.0]
+            {
+                for arg in &invocation.args {
+                    types.remove(arg);
+                }
+
+                let libfunc = registry.get_libfunc(&invocation.libfunc_id).unwrap();
+                for (branch, signature) in
+                    invocation.branches.iter().zip(libfunc.branch_signatures())
+                {
+                    let mut types = types.clone();
+                    types.extend(
+                        branch
+                            .results
+                            .iter()
+                            .cloned()
+                            .zip(signature.vars.iter().map(|var| var.ty.clone())),
+                    );
+                    pending.push((statement_idx.next(&branch.target), types));
+                }
+            }
+        }
+
+        HashMap::new()
+    }
+}
+
+pub(crate) fn function_matches(function_id: &FunctionId, name: &str) -> bool {
+    match function_id.debug_name.as_deref() {
+        Some(debug_name) => {
+            debug_name == name
+                || debug_name
+                    .rsplit_once("::")
+                    .is_some_and(|(_, last)| last == name)
+        }
+        None => function_id.id.to_string() == name,
+    }
+}
+
+/// Render a value using its Sierra type.
+pub fn render_value(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    ty: &ConcreteTypeId,
+    value: &Value,
+) -> String {
+    let ty = find_real_type(registry, ty);
+    match (registry.get_type(&ty).unwrap(), value) {
+        (CoreTypeConcrete::NonZero(info), _) => render_value(registry, &info.ty, value),
+        (CoreTypeConcrete::Enum(info), Value::Enum { index, payload, .. }) => format!(
+            "{ty}::{index}({})",
+            render_value(registry, &info.variants[*index], payload)
+        ),
+        (CoreTypeConcrete::Struct(info), Value::Struct(members)) if members.is_empty() => {
+            if info.members.is_empty() {
+                "()".to_string()
+            } else {
+                format!("{value:?}")
+            }
+        }
+        (CoreTypeConcrete::Struct(info), Value::Struct(members)) => format!(
+            "{ty} {{ {} }}",
+            info.members
+                .iter()
+                .zip(members)
+                .map(|(ty, value)| render_value(registry, ty, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        (_, Value::Array { ty, data }) => format!(
+            "[{}]",
+            data.iter()
+                .map(|value| render_value(registry, ty, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        (_, Value::Felt(value)) => value.to_string(),
+        (_, Value::Bytes31(value)) => value.to_hex_string(),
+        (_, Value::BoundedInt { value, .. }) => value.to_string(),
+        (_, Value::U8(value)) => value.to_string(),
+        (_, Value::U16(value)) => value.to_string(),
+        (_, Value::U32(value)) => value.to_string(),
+        (_, Value::U64(value)) => value.to_string(),
+        (_, Value::U128(value)) => value.to_string(),
+        (_, Value::U256(lo, hi)) => format!("{{ low: {lo}, high: {hi} }}"),
+        (_, Value::I8(value)) => value.to_string(),
+        (_, Value::I16(value)) => value.to_string(),
+        (_, Value::I32(value)) => value.to_string(),
+        (_, Value::I64(value)) => value.to_string(),
+        (_, Value::I128(value)) => value.to_string(),
+        _ => format!("{value:?}"),
+    }
+}
+
+impl Display for FrameInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at statement {}",
+            self.function_id, self.statement_idx.0
+        )?;
+        if let Some(location) = &self.location {
+            write!(f, " ({location})")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starknet::StubSyscallHandler;
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::Arc;
+
+    fn debugger() -> Debugger {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Tuple<felt252, felt252> = Struct<ut@Tuple, felt252, felt252>;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@double> = function_call<user@double>;
+                    libfunc struct_construct<Tuple<felt252, felt252>> = struct_construct<Tuple<felt252, felt252>>;
+                    libfunc store_temp<Tuple<felt252, felt252>> = store_temp<Tuple<felt252, felt252>>;
+
+                    dup<felt252>([0]) -> ([0], [1]);
+                    function_call<user@double>([1]) -> ([2]);
+                    struct_construct<Tuple<felt252, felt252>>([0], [2]) -> ([3]);
+                    store_temp<Tuple<felt252, felt252>>([3]) -> ([3]);
+                    return([3]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (Tuple<felt252, felt252>);
+                    double@5([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+        Debugger::new(vm)
+    }
+
+    #[test]
+    fn test_breakpoints_and_stepping() {
+        let mut debugger = debugger();
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        debugger.add_breakpoint(Breakpoint::Function("double".to_string()));
+        debugger.add_breakpoint(Breakpoint::Libfunc("felt252_add".to_string()));
+
+        assert_eq!(debugger.resume(syscall_handler), StopReason::Breakpoint(0));
+        assert_eq!(debugger.current_statement(), Some(StatementIdx(5)));
+        assert_eq!(debugger.backtrace().len(), 2);
+        assert_eq!(debugger.print("[0]").unwrap(), "felt252 = 21");
+
+        assert_eq!(debugger.resume(syscall_handler), StopReason::Breakpoint(1));
+        assert_eq!(debugger.current_statement(), Some(StatementIdx(6)));
+
+        assert_eq!(debugger.finish(syscall_handler), StopReason::Step);
+        assert_eq!(debugger.current_statement(), Some(StatementIdx(2)));
+        assert_eq!(debugger.print("2").unwrap(), "felt252 = 42");
+
+        assert_eq!(debugger.next(syscall_handler), StopReason::Step);
+        assert_eq!(
+            debugger.print("[3]").unwrap(),
+            "Tuple<felt252, felt252> = Tuple<felt252, felt252> { 21, 42 }"
+        );
+
+        assert_eq!(debugger.resume(syscall_handler), StopReason::Finished);
+        assert!(debugger.is_finished());
+        assert_eq!(debugger.return_values().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_next_steps_over_calls() {
+        let mut debugger = debugger();
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        assert_eq!(debugger.next(syscall_handler), StopReason::Step);
+        assert_eq!(debugger.next(syscall_handler), StopReason::Step);
+        assert_eq!(debugger.current_statement(), Some(StatementIdx(2)));
+        assert_eq!(debugger.backtrace().len(), 1);
+    }
+}
@@ -1,19 +1,35 @@
-use crate::value::Value;
+use crate::{
+    output::PrintedOutput, panic::format_panic_data, source_map::SourceLocation, value::Value,
+};
 use cairo_lang_sierra::{ids::VarId, program::StatementIdx};
 use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
-use serde::{ser::SerializeMap, Serialize};
+use serde::{ser::SerializeMap, Deserialize, Serialize};
 use starknet_crypto::Felt;
 use std::collections::BTreeMap;
 
-#[derive(Clone, Debug, Default, Serialize)]
+pub use self::compact::{CompactStep, CompactTrace};
+
+mod compact;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProgramTrace {
     pub states: Vec<StateDump>,
+    /// Cairo code locations of the executed statements, when the program has them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<StatementIdx, SourceLocation>,
+    /// Text printed by the program, when captured.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<PrintedOutput>,
     // TODO: Syscall data.
 }
 
 impl ProgramTrace {
     pub fn new() -> Self {
-        Self { states: Vec::new() }
+        Self {
+            states: Vec::new(),
+            locations: BTreeMap::new(),
+            output: Vec::new(),
+        }
     }
 
     pub fn push(&mut self, state: StateDump) {
@@ -21,7 +37,7 @@ impl ProgramTrace {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StateDump {
     pub statement_idx: StatementIdx,
     pub items: BTreeMap<u64, Value>,
@@ -53,6 +69,26 @@ impl Serialize for StateDump {
     }
 }
 
+impl<'de> Deserialize<'de> for StateDump {
+    fn deserialize<D>(d: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RawStateDump {
+            statement_idx: usize,
+            pre_state_dump: BTreeMap<u64, Value>,
+        }
+
+        let raw = RawStateDump::deserialize(d)?;
+        Ok(Self {
+            statement_idx: StatementIdx(raw.statement_idx),
+            items: raw.pre_state_dump,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ContractExecutionResult {
     pub remaining_gas: u64,
@@ -101,14 +137,7 @@ impl ContractExecutionResult {
                                     error_felt_vec.push(*x);
                                 }
                             }
-                            let bytes_err: Vec<_> = error_felt_vec
-                                .iter()
-                                .flat_map(|felt| felt.to_bytes_be().to_vec())
-                                // remove null chars
-                                .filter(|b| *b != 0)
-                                .collect();
-                            let str_error = String::from_utf8(bytes_err).unwrap().to_owned();
-                            error_msg = Some(str_error);
+                            error_msg = Some(format_panic_data(&error_felt_vec));
                         }
                     }
                 }
@@ -0,0 +1,301 @@
+use super::{ProgramTrace, StateDump};
+use crate::{output::PrintedOutput, source_map::SourceLocation, value::Value};
+use cairo_lang_sierra::program::StatementIdx;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+/// A delta-encoded program trace.
+///
+/// Instead of storing every live variable at every statement, each step only stores the variables
+/// that stopped being live (or changed) since the previous step and the values that replaced them.
+/// Full [`StateDump`]s are reconstructed on demand by replaying the steps.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactTrace {
+    pub steps: Vec<CompactStep>,
+    /// Cairo code locations of the executed statements, when the program has them.
+    pub locations: BTreeMap<StatementIdx, SourceLocation>,
+    /// Text printed by the program, when captured.
+    pub output: Vec<PrintedOutput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactStep {
+    pub statement_idx: StatementIdx,
+    /// Variables from the previous state which are not present in this one.
+    pub consumed: Vec<u64>,
+    /// Variables which were not present in the previous state, or whose value has changed.
+    pub produced: Vec<(u64, Value)>,
+}
+
+impl CompactStep {
+    /// Compute the step which transforms `prev` into `next`.
+    pub fn diff(
+        statement_idx: StatementIdx,
+        prev: &BTreeMap<u64, Value>,
+        next: &BTreeMap<u64, Value>,
+    ) -> Self {
+        let consumed = prev
+            .iter()
+            .filter(|(id, value)| next.get(id) != Some(value))
+            .map(|(id, _)| *id)
+            .collect();
+        let produced = next
+            .iter()
+            .filter(|(id, value)| prev.get(id) != Some(value))
+            .map(|(id, value)| (*id, value.clone()))
+            .collect();
+
+        Self {
+            statement_idx,
+            consumed,
+            produced,
+        }
+    }
+
+    /// Apply the step to the previous state, turning it into the state of this step.
+    pub fn apply(&self, state: &mut BTreeMap<u64, Value>) {
+        for id in &self.consumed {
+            state.remove(id);
+        }
+        state.extend(self.produced.iter().cloned());
+    }
+}
+
+impl CompactTrace {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            locations: BTreeMap::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, step: CompactStep) {
+        self.steps.push(step);
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Lazily reconstruct the full state of every step.
+    pub fn states(&self) -> impl Iterator<Item = StateDump> + '_ {
+        let mut items = BTreeMap::new();
+        self.steps.iter().map(move |step| {
+            step.apply(&mut items);
+            StateDump {
+                statement_idx: step.statement_idx,
+                items: items.clone(),
+            }
+        })
+    }
+
+    /// Reconstruct the full state of the step at `index`.
+    pub fn state_at(&self, index: usize) -> Option<StateDump> {
+        let step = self.steps.get(index)?;
+
+        let mut items = BTreeMap::new();
+        for step in &self.steps[..=index] {
+            step.apply(&mut items);
+        }
+
+        Some(StateDump {
+            statement_idx: step.statement_idx,
+            items,
+        })
+    }
+
+    /// Write the trace using its binary encoding.
+    pub fn write_to(&self, writer: impl Write) -> bincode::Result<()> {
+        bincode::serialize_into(writer, self)
+    }
+
+    /// Read a trace from its binary encoding.
+    pub fn read_from(reader: impl Read) -> bincode::Result<Self> {
+        bincode::deserialize_from(reader)
+    }
+}
+
+impl From<&ProgramTrace> for CompactTrace {
+    fn from(trace: &ProgramTrace) -> Self {
+        let mut prev = BTreeMap::new();
+        let steps = trace
+            .states
+            .iter()
+            .map(|state| {
+                let step = CompactStep::diff(state.statement_idx, &prev, &state.items);
+                prev.clone_from(&state.items);
+                step
+            })
+            .collect();
+
+        Self {
+            steps,
+            locations: trace.locations.clone(),
+            output: trace.output.clone(),
+        }
+    }
+}
+
+impl From<&CompactTrace> for ProgramTrace {
+    fn from(trace: &CompactTrace) -> Self {
+        Self {
+            states: trace.states().collect(),
+            locations: trace.locations.clone(),
+            output: trace.output.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        output::OutputSink,
+        source_map::{SourceMap, SourcePosition},
+        starknet::StubSyscallHandler,
+        VirtualMachine,
+    };
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::Arc;
+
+    fn run_trace() -> ProgramTrace {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc felt252_mul = felt252_mul;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    dup<felt252>([0]) -> ([0], [2]);
+                    felt252_add([0], [1]) -> ([3]);
+                    felt252_mul([2], [3]) -> ([4]);
+                    store_temp<felt252>([4]) -> ([4]);
+                    return([4]);
+
+                    main@0([0]: felt252, [1]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.call_program(
+            &program.funcs[0],
+            0,
+            [Value::Felt(2.into()), Value::Felt(3.into())],
+        );
+
+        vm.run_with_trace(&mut StubSyscallHandler::default())
+    }
+
+    #[test]
+    fn test_compact_roundtrip() {
+        let trace = run_trace();
+        let compact = CompactTrace::from(&trace);
+
+        assert_eq!(compact.len(), trace.states.len());
+        assert_eq!(compact.steps[2].consumed, vec![0, 1]);
+        assert_eq!(compact.steps[2].produced, vec![(3, Value::Felt(5.into()))]);
+
+        let restored = ProgramTrace::from(&compact);
+        for (expected, actual) in trace.states.iter().zip(&restored.states) {
+            assert_eq!(expected.statement_idx, actual.statement_idx);
+            assert_eq!(expected.items, actual.items);
+        }
+
+        let last = compact.state_at(compact.len() - 1).unwrap();
+        assert_eq!(last.items, trace.states.last().unwrap().items);
+    }
+
+    #[test]
+    fn test_compact_binary_and_json() {
+        let trace = run_trace();
+        let compact = CompactTrace::from(&trace);
+
+        let mut buffer = Vec::new();
+        compact.write_to(&mut buffer).unwrap();
+        assert_eq!(CompactTrace::read_from(buffer.as_slice()).unwrap(), compact);
+
+        let json = serde_json::to_string(&trace).unwrap();
+        let parsed: ProgramTrace = serde_json::from_str(&json).unwrap();
+        assert_eq!(CompactTrace::from(&parsed), compact);
+    }
+
+    #[test]
+    fn test_compact_keeps_locations_and_output() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Unit = Struct<ut@Tuple>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc felt252_const<26982> = felt252_const<26982>;
+                    libfunc print = print;
+                    libfunc struct_construct<Unit> = struct_construct<Unit>;
+                    libfunc store_temp<Unit> = store_temp<Unit>;
+
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<26982>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    print([0]) -> ();
+                    struct_construct<Unit>() -> ([2]);
+                    store_temp<Unit>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0() -> (Unit);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut source_map = SourceMap::new();
+        source_map.insert(
+            StatementIdx(3),
+            SourceLocation {
+                file: "lib.cairo".to_string(),
+                start: SourcePosition { line: 4, col: 4 },
+                end: SourcePosition { line: 4, col: 20 },
+                in_macro: true,
+            },
+        );
+
+        let run = |compact: bool| {
+            let mut vm = VirtualMachine::new(program.clone());
+            vm.set_source_map(source_map.clone());
+            vm.set_output_sink(OutputSink::Capture);
+            vm.call_program(&program.funcs[0], 0, []);
+
+            let syscall_handler = &mut StubSyscallHandler::default();
+            if compact {
+                ProgramTrace::from(&vm.run_with_compact_trace(syscall_handler))
+            } else {
+                vm.run_with_trace(syscall_handler)
+            }
+        };
+
+        let trace = run(false);
+        assert_eq!(trace.locations.len(), 1);
+        assert_eq!(trace.output.len(), 1);
+        assert_eq!(run(true), trace);
+
+        let compact = CompactTrace::from(&trace);
+        let mut buffer = Vec::new();
+        compact.write_to(&mut buffer).unwrap();
+        let compact = CompactTrace::read_from(buffer.as_slice()).unwrap();
+        assert_eq!(ProgramTrace::from(&compact), trace);
+    }
+}
@@ -2,20 +2,30 @@ use std::{fmt::Debug, ops::Deref};
 
 use cairo_lang_runner::token_gas_cost;
 use cairo_lang_sierra::{
-    extensions::gas::CostTokenType,
-    ids::FunctionId,
+    extensions::{
+        circuit::{CircuitInfo, CircuitTypeConcrete, ConcreteCircuit},
+        core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+        gas::CostTokenType,
+    },
+    ids::{ConcreteTypeId, FunctionId},
     program::{Program, StatementIdx},
+    program_registry::ProgramRegistry,
 };
 use cairo_lang_sierra_ap_change::{ap_change_info::ApChangeInfo, ApChangeError};
-use cairo_lang_sierra_gas::{gas_info::GasInfo, CostError};
+use cairo_lang_sierra_gas::{
+    core_libfunc_cost::{core_libfunc_cost, InvocationCostInfoProvider},
+    gas_info::GasInfo,
+    CostError,
+};
 use cairo_lang_sierra_to_casm::metadata::{
     calc_metadata, calc_metadata_ap_change_only, Metadata as CairoGasMetadata,
     MetadataComputationConfig, MetadataError as CairoGasMetadataError,
 };
-use cairo_lang_utils::casts::IntoOrPanic;
-use serde::Serialize;
+use cairo_lang_sierra_type_size::TypeSizeMap;
+use cairo_lang_utils::{casts::IntoOrPanic, ordered_hash_map::OrderedHashMap};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BuiltinCosts {
     pub r#const: u64,
     pub pedersen: u64,
@@ -56,6 +66,34 @@ impl From<BuiltinCosts> for [u64; 7] {
     }
 }
 
+/// The tokens pre-charged for a function call and not yet consumed by its statements.
+pub type GasWallet = OrderedHashMap<CostTokenType, i64>;
+
+impl BuiltinCosts {
+    /// Returns the gas the tokens of `wallet` are worth.
+    pub fn wallet_gas(&self, wallet: &GasWallet) -> i128 {
+        wallet
+            .iter()
+            .map(|(token_type, count)| {
+                // Like in the CASM implementation, const tokens aren't priced through the cost
+                // table.
+                let token_cost = match token_type {
+                    CostTokenType::Const => 1,
+                    CostTokenType::Pedersen => self.pedersen,
+                    CostTokenType::Bitwise => self.bitwise,
+                    CostTokenType::EcOp => self.ecop,
+                    CostTokenType::Poseidon => self.poseidon,
+                    CostTokenType::AddMod => self.add_mod,
+                    CostTokenType::MulMod => self.mul_mod,
+                    _ => panic!(),
+                };
+
+                *count as i128 * token_cost as i128
+            })
+            .sum()
+    }
+}
+
 /// Holds global gas info.
 #[derive(Default)]
 pub struct GasMetadata(pub CairoGasMetadata);
@@ -148,6 +186,43 @@ impl GasMetadata {
         )
     }
 
+    /// Returns the gas wallet at the entry point of `func`, which is empty when no gas equations
+    /// were solved.
+    pub fn initial_wallet(&self, func: &FunctionId) -> GasWallet {
+        self.gas_info
+            .function_costs
+            .get(func)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the tokens consumed from the gas wallet by every branch of the invocation at
+    /// `idx`, the same way the CASM compiler computes them. It's empty when no gas equations were
+    /// solved.
+    pub fn get_invocation_costs(
+        &self,
+        registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+        type_sizes: &TypeSizeMap,
+        idx: StatementIdx,
+        libfunc: &CoreConcreteLibfunc,
+    ) -> Vec<GasWallet> {
+        if self.gas_info.function_costs.is_empty() {
+            return Vec::new();
+        }
+
+        core_libfunc_cost(
+            &self.gas_info,
+            &idx,
+            libfunc,
+            &CostInfo {
+                registry,
+                type_sizes,
+                gas: self,
+                idx,
+            },
+        )
+    }
+
     pub fn get_gas_costs_for_statement(&self, idx: StatementIdx) -> Vec<(u64, CostTokenType)> {
         let mut costs = Vec::new();
         for cost_type in CostTokenType::iter_casm_tokens() {
@@ -179,6 +254,44 @@ impl GasMetadata {
     }
 }
 
+struct CostInfo<'a> {
+    registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    type_sizes: &'a TypeSizeMap,
+    gas: &'a GasMetadata,
+    idx: StatementIdx,
+}
+
+impl InvocationCostInfoProvider for CostInfo<'_> {
+    fn type_size(&self, ty: &ConcreteTypeId) -> usize {
+        self.type_sizes[ty] as usize
+    }
+
+    fn token_usages(&self, token_type: CostTokenType) -> usize {
+        self.gas
+            .get_gas_cost_for_statement_and_cost_token_type(self.idx, token_type)
+            .unwrap_or(0) as usize
+    }
+
+    fn ap_change_var_value(&self) -> usize {
+        self.gas
+            .ap_change_info
+            .variable_values
+            .get(&self.idx)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn circuit_info(&self, ty: &ConcreteTypeId) -> &CircuitInfo {
+        match self.registry.get_type(ty).unwrap() {
+            CoreTypeConcrete::Circuit(CircuitTypeConcrete::Circuit(ConcreteCircuit {
+                circuit_info,
+                ..
+            })) => circuit_info,
+            _ => panic!("{ty} is not a circuit"),
+        }
+    }
+}
+
 impl Clone for GasMetadata {
     fn clone(&self) -> Self {
         Self(CairoGasMetadata {
@@ -5,15 +5,32 @@ use cairo_lang_sierra::{
     program_registry::ProgramRegistry,
 };
 
-pub use self::{dump::*, gas::BuiltinCosts, value::*, vm::VirtualMachine};
+pub use self::{
+    dump::*,
+    gas::BuiltinCosts,
+    resources::ExecutionResources,
+    value::*,
+    vm::{CallFrame, VirtualMachine, VmSnapshot},
+};
 
+pub mod coverage;
+pub mod dap;
 mod debug;
+pub mod debugger;
 mod dump;
 mod gas;
+pub mod observer;
+pub mod output;
+pub mod panic;
+pub mod profiler;
+mod resources;
+pub mod source_map;
 pub mod starknet;
+pub mod support;
 mod test_utils;
 mod value;
 mod vm;
+pub mod watch;
 
 pub fn find_entry_point_by_idx(
     program: &Program,
@@ -1,34 +1,63 @@
-use self::args::CmdArgs;
+use self::args::{CheckArgs, CmdArgs, Command, ProgramArgs, RunArgs, TraceFormat};
 use cairo_lang_sierra::{
+    debug_info::DebugInfo,
     extensions::{
         circuit::CircuitTypeConcrete, core::CoreTypeConcrete, starknet::StarknetTypeConcrete,
     },
+    program_registry::ProgramRegistry,
     ProgramParser,
 };
-use clap::Parser;
-use sierra_emu::{starknet::StubSyscallHandler, Value, VirtualMachine};
+use clap::{CommandFactory, Parser};
+use sierra_emu::{
+    coverage::Coverage, debugger::Debugger, output::OutputSink, panic::PanicTracker,
+    source_map::SourceMap, starknet::StubSyscallHandler, support::UnsupportedReport, Value,
+    VirtualMachine,
+};
 use std::{
     fs::{self, File},
-    io::stdout,
-    sync::Arc,
+    io::{stderr, stdin, stdout},
+    sync::{Arc, Mutex},
 };
-use tracing::{debug, info, Level};
+use tracing::{debug, info, warn, Level};
 use tracing_subscriber::{EnvFilter, FmtSubscriber};
 
 mod args;
+mod repl;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = CmdArgs::parse();
 
-    tracing::subscriber::set_global_default(
-        FmtSubscriber::builder()
-            .with_env_filter(EnvFilter::from_default_env())
-            .with_max_level(Level::TRACE)
-            .finish(),
-    )?;
+    let subscriber = FmtSubscriber::builder()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_max_level(Level::TRACE);
+    match args.command {
+        // Stdout is reserved for the protocol messages.
+        Some(Command::Dap) => tracing::subscriber::set_global_default(
+            subscriber.with_writer(std::io::stderr).finish(),
+        )?,
+        _ => tracing::subscriber::set_global_default(subscriber.finish())?,
+    }
+
+    match (args.command, args.program) {
+        (Some(Command::Debug(args)), _) => {
+            let mut vm = load_program(&args)?;
+            vm.enable_history();
+            push_entry_point(&mut vm, args)?;
+            repl::run(Debugger::new(vm))
+        }
+        (Some(Command::Dap), _) => Ok(sierra_emu::dap::serve(stdin().lock(), stdout().lock())?),
+        (Some(Command::Check(args)), _) => check(args),
+        (None, Some(program)) => run(program, args.run),
+        (None, None) => {
+            CmdArgs::command().print_help()?;
+            Ok(())
+        }
+    }
+}
 
+fn load_program(args: &ProgramArgs) -> Result<VirtualMachine, Box<dyn std::error::Error>> {
     info!("Loading the Sierra program from disk.");
-    let source_code = fs::read_to_string(args.program)?;
+    let source_code = fs::read_to_string(&args.program)?;
 
     info!("Parsing the Sierra program.");
     let program = Arc::new(
@@ -38,9 +67,48 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     info!("Preparing the virtual machine.");
-    let mut vm = VirtualMachine::new(program.clone());
+    let mut vm = VirtualMachine::new(program);
+    vm.set_seed(args.seed);
+    let unsupported = vm.unsupported();
+    if !unsupported.is_empty() {
+        warn!("The program uses libfuncs or types the emulator can't execute:\n{unsupported}");
+    }
+    if let Some(path) = &args.debug_info {
+        let debug_info: DebugInfo = serde_json::from_reader(File::open(path)?)?;
+        vm.set_source_map(SourceMap::from_debug_info(&debug_info)?);
+    }
+
+    Ok(vm)
+}
+
+fn check(args: CheckArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let source_code = fs::read_to_string(&args.program)?;
+    let program = ProgramParser::new()
+        .parse(&source_code)
+        .map_err(|e| e.to_string())?;
+    let registry = ProgramRegistry::new(&program)?;
+
+    let report = UnsupportedReport::new(&program, &registry);
+    if args.json {
+        serde_json::to_writer_pretty(stdout().lock(), &report)?;
+        println!();
+    } else {
+        print!("{report}");
+    }
+
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err("the program can't be fully executed by the emulator".into())
+    }
+}
 
+fn push_entry_point(
+    vm: &mut VirtualMachine,
+    args: ProgramArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
     debug!("Pushing the entry point's frame.");
+    let program = vm.program.clone();
     let function = program
         .funcs
         .iter()
@@ -48,51 +116,127 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             args::EntryPoint::Number(x) => f.id.id == *x,
             args::EntryPoint::String(x) => f.id.debug_name.as_deref() == Some(x.as_str()),
         })
-        .unwrap();
+        .ok_or("entry point not found")?;
 
     debug!(
         "Entry point argument types: {:?}",
         function.signature.param_types
     );
     let mut iter = args.args.into_iter();
-    vm.push_frame(
-        function.id.clone(),
-        function
-            .signature
-            .param_types
-            .iter()
-            .map(|type_id| {
-                let type_info = vm.registry().get_type(type_id).unwrap();
-                match type_info {
-                    CoreTypeConcrete::Felt252(_) => Value::parse_felt(&iter.next().unwrap()),
-                    CoreTypeConcrete::GasBuiltin(_) => Value::U64(args.available_gas.unwrap()),
-                    CoreTypeConcrete::RangeCheck(_)
-                    | CoreTypeConcrete::RangeCheck96(_)
-                    | CoreTypeConcrete::Bitwise(_)
-                    | CoreTypeConcrete::Pedersen(_)
-                    | CoreTypeConcrete::Poseidon(_)
-                    | CoreTypeConcrete::SegmentArena(_)
-                    | CoreTypeConcrete::Circuit(
-                        CircuitTypeConcrete::AddMod(_) | CircuitTypeConcrete::MulMod(_),
-                    ) => Value::Unit,
-                    CoreTypeConcrete::Starknet(inner) => match inner {
-                        StarknetTypeConcrete::System(_) => Value::Unit,
-                        _ => todo!(),
-                    },
+    let values = function
+        .signature
+        .param_types
+        .iter()
+        .map(|type_id| {
+            let type_info = vm.registry().get_type(type_id).unwrap();
+            match type_info {
+                CoreTypeConcrete::Felt252(_) => Value::parse_felt(&iter.next().unwrap()),
+                CoreTypeConcrete::GasBuiltin(_) => Value::U64(args.available_gas.unwrap()),
+                CoreTypeConcrete::RangeCheck(_)
+                | CoreTypeConcrete::RangeCheck96(_)
+                | CoreTypeConcrete::Bitwise(_)
+                | CoreTypeConcrete::Pedersen(_)
+                | CoreTypeConcrete::Poseidon(_)
+                | CoreTypeConcrete::SegmentArena(_)
+                | CoreTypeConcrete::Circuit(
+                    CircuitTypeConcrete::AddMod(_) | CircuitTypeConcrete::MulMod(_),
+                ) => Value::Unit,
+                CoreTypeConcrete::Starknet(inner) => match inner {
+                    StarknetTypeConcrete::System(_) => Value::Unit,
                     _ => todo!(),
-                }
-            })
-            .collect::<Vec<_>>(),
-    );
+                },
+                _ => todo!(),
+            }
+        })
+        .collect::<Vec<_>>();
+    vm.push_frame(function.id.clone(), values);
+
+    Ok(())
+}
+
+fn run(program: ProgramArgs, args: RunArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let mut vm = load_program(&program)?;
+    if args.profile || args.profile_folded.is_some() {
+        vm.enable_profiler();
+    }
+    if args.gas_profile.is_some() || args.gas_profile_folded.is_some() {
+        vm.enable_gas_profiler();
+    }
+    let coverage = (args.coverage.is_some() || args.lcov.is_some()).then(|| {
+        let coverage = Arc::new(Mutex::new(Coverage::new()));
+        vm.add_observer(coverage.clone());
+        coverage
+    });
+    let panic_tracker = Arc::new(Mutex::new(PanicTracker::new()));
+    vm.add_observer(panic_tracker.clone());
+    if args.output.is_none() {
+        // Keep the printed text out of the trace written to stdout.
+        vm.set_output_sink(OutputSink::Writer(Arc::new(Mutex::new(stderr()))));
+    }
+    push_entry_point(&mut vm, program)?;
 
     info!("Running the program.");
     let syscall_handler = &mut StubSyscallHandler::default();
-    let trace = vm.run_with_trace(syscall_handler);
+    match args.trace_format {
+        TraceFormat::Json => {
+            let trace = vm.run_with_trace(syscall_handler);
+            match args.output {
+                Some(path) => serde_json::to_writer(File::create(path)?, &trace)?,
+                None => serde_json::to_writer(stdout().lock(), &trace)?,
+            };
+        }
+        TraceFormat::Compact => {
+            let trace = vm.run_with_compact_trace(syscall_handler);
+            match args.output {
+                Some(path) => trace.write_to(File::create(path)?)?,
+                None => trace.write_to(stdout().lock())?,
+            };
+        }
+    }
+
+    let panic_tracker = panic_tracker.lock().unwrap();
+    if let (Some(message), Some(backtrace)) = (panic_tracker.message(), panic_tracker.backtrace()) {
+        eprintln!("Panicked with: {message}");
+        for (i, (function_id, statement_idx)) in backtrace.iter().enumerate() {
+            match vm.source_map().and_then(|map| map.location(*statement_idx)) {
+                Some(location) => eprintln!(
+                    "#{i} {function_id} at statement {} ({location})",
+                    statement_idx.0
+                ),
+                None => eprintln!("#{i} {function_id} at statement {}", statement_idx.0),
+            }
+        }
+    }
 
-    match args.output {
-        Some(path) => serde_json::to_writer(File::create(path)?, &trace)?,
-        None => serde_json::to_writer(stdout().lock(), &trace)?,
-    };
+    if let Some(profiler) = vm.profiler() {
+        if args.profile {
+            eprintln!("{profiler}");
+        }
+        if let Some(path) = args.profile_folded {
+            fs::write(path, profiler.folded_stacks())?;
+        }
+    }
+    if let Some(gas_profiler) = vm.gas_profiler() {
+        if let Some(path) = args.gas_profile {
+            serde_json::to_writer_pretty(File::create(path)?, &gas_profiler.report())?;
+        }
+        if let Some(path) = args.gas_profile_folded {
+            fs::write(path, gas_profiler.folded_stacks())?;
+        }
+    }
+    if let Some(path) = args.resources {
+        serde_json::to_writer_pretty(File::create(path)?, vm.execution_resources())?;
+    }
+    if let Some(coverage) = coverage {
+        let coverage = coverage.lock().unwrap();
+        if let Some(path) = args.coverage {
+            serde_json::to_writer_pretty(File::create(path)?, &coverage.report(&vm.program))?;
+        }
+        if let Some(path) = args.lcov {
+            let source_map = vm.source_map().ok_or("--lcov requires --debug-info")?;
+            fs::write(path, coverage.lcov(&vm.program, source_map))?;
+        }
+    }
 
     Ok(())
 }
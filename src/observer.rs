@@ -0,0 +1,267 @@
+use crate::{debug::libfunc_to_name, source_map::SourceLocation, Value};
+use cairo_lang_sierra::{
+    extensions::core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+    ids::{ConcreteTypeId, FunctionId, UserTypeId, VarId},
+    program::{GenericArg, Statement, StatementIdx},
+    program_registry::ProgramRegistry,
+};
+use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use starknet_types_core::felt::Felt;
+use tracing::{debug, trace};
+
+/// Callbacks invoked by the [`VirtualMachine`](crate::VirtualMachine) while it executes a
+/// program. Every method does nothing by default.
+pub trait VmObserver {
+    /// Called before executing a statement.
+    fn before_statement(&mut self, _statement: &StatementContext) {}
+
+    /// Called after a libfunc has been invoked, unless it was a function call.
+    fn after_invocation(&mut self, _invocation: &InvocationContext) {}
+
+    /// Whether the observer implements [`VmObserver::after_invocation`] or
+    /// [`VmObserver::on_syscall`]. When none does, the arguments of the invocations aren't kept
+    /// around for them.
+    fn observes_invocations(&self) -> bool {
+        true
+    }
+
+    /// Called after a syscall, along with [`VmObserver::after_invocation`].
+    fn on_syscall(&mut self, _invocation: &InvocationContext) {}
+
+    /// Called when a frame is pushed, including the entry point's.
+    fn on_function_call(&mut self, _function_id: &FunctionId, _args: &[Value]) {}
+
+    /// Called when a function returns.
+    fn on_return(&mut self, _function_id: &FunctionId, _values: &[Value]) {}
+
+    /// Called when a function returns a panic, right before [`VmObserver::on_return`]. Panics
+    /// propagate through every frame, so this is called for each of them, innermost first.
+    fn on_panic(&mut self, _function_id: &FunctionId, _panic_data: &[Felt]) {}
+}
+
+/// The statement about to be executed.
+#[derive(Clone, Copy)]
+pub struct StatementContext<'a> {
+    pub statement_idx: StatementIdx,
+    pub statement: &'a Statement,
+    pub function_id: &'a FunctionId,
+    /// The variables of the current frame.
+    pub state: &'a OrderedHashMap<VarId, Value>,
+    /// The libfunc invoked by the statement, if it isn't a return.
+    pub libfunc: Option<&'a CoreConcreteLibfunc>,
+    pub location: Option<&'a SourceLocation>,
+}
+
+/// A libfunc invocation that has just been executed.
+#[derive(Clone, Copy)]
+pub struct InvocationContext<'a> {
+    pub registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    pub statement_idx: StatementIdx,
+    pub libfunc: &'a CoreConcreteLibfunc,
+    pub args: &'a [Value],
+    pub branch_idx: usize,
+    pub results: &'a [Value],
+}
+
+/// Logs the executed statements using `tracing`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TracingObserver;
+
+impl VmObserver for TracingObserver {
+    fn before_statement(&mut self, statement: &StatementContext) {
+        match statement.location {
+            Some(location) => debug!(
+                "Evaluating statement {} ({location}): {}",
+                statement.statement_idx.0, statement.statement
+            ),
+            None => debug!(
+                "Evaluating statement {}: {}",
+                statement.statement_idx.0, statement.statement
+            ),
+        }
+        trace!("values: \n{:#?}\n", statement.state);
+        if let Some(libfunc) = statement.libfunc {
+            debug!(
+                "Executing invocation of libfunc: {}",
+                libfunc_to_name(libfunc)
+            );
+        }
+    }
+
+    fn observes_invocations(&self) -> bool {
+        false
+    }
+}
+
+/// The panic data of a returned `PanicResult::Err`, which holds a `Panic` marker and an array of
+/// felts.
+pub(crate) fn panic_data(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    value: &Value,
+) -> Option<Vec<Felt>> {
+    let Value::Enum {
+        self_ty,
+        index: 1,
+        payload,
+    } = value
+    else {
+        return None;
+    };
+    if !is_panic_result(registry, self_ty) {
+        return None;
+    }
+    let Value::Struct(members) = &**payload else {
+        return None;
+    };
+    let [_, Value::Array { data, .. }] = members.as_slice() else {
+        return None;
+    };
+
+    data.iter()
+        .map(|value| match value {
+            Value::Felt(felt) => Some(*felt),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether the enum is a `PanicResult`, whose error variant starts with `core::panics::Panic`.
+/// Other enums may have the same shape, so it goes by the type rather than the value.
+fn is_panic_result(registry: &ProgramRegistry<CoreType, CoreLibfunc>, ty: &ConcreteTypeId) -> bool {
+    let Ok(CoreTypeConcrete::Enum(info)) = registry.get_type(ty) else {
+        return false;
+    };
+    let Some(Ok(CoreTypeConcrete::Struct(error))) =
+        info.variants.get(1).map(|ty| registry.get_type(ty))
+    else {
+        return false;
+    };
+    let Some(Ok(CoreTypeConcrete::Struct(marker))) =
+        error.members.first().map(|ty| registry.get_type(ty))
+    else {
+        return false;
+    };
+
+    matches!(
+        marker.info.long_id.generic_args.first(),
+        Some(GenericArg::UserType(name)) if *name == UserTypeId::from_string("core::panics::Panic")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{starknet::StubSyscallHandler, VirtualMachine};
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        statements: Vec<usize>,
+        invocations: Vec<(usize, usize)>,
+        calls: Vec<String>,
+        returns: Vec<(String, Vec<Value>)>,
+    }
+
+    impl VmObserver for Recorder {
+        fn before_statement(&mut self, statement: &StatementContext) {
+            self.statements.push(statement.statement_idx.0);
+        }
+
+        fn after_invocation(&mut self, invocation: &InvocationContext) {
+            self.invocations
+                .push((invocation.statement_idx.0, invocation.results.len()));
+        }
+
+        fn on_function_call(&mut self, function_id: &FunctionId, _args: &[Value]) {
+            self.calls.push(function_id.to_string());
+        }
+
+        fn on_return(&mut self, function_id: &FunctionId, values: &[Value]) {
+            self.returns
+                .push((function_id.to_string(), values.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_observer_callbacks() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@double> = function_call<user@double>;
+
+                    function_call<user@double>([0]) -> ([1]);
+                    store_temp<felt252>([1]) -> ([1]);
+                    return([1]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (felt252);
+                    double@3([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.add_observer(recorder.clone());
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.statements, [0, 3, 4, 5, 6, 1, 2]);
+        assert_eq!(recorder.invocations, [(3, 2), (4, 1), (5, 1), (1, 1)]);
+        assert_eq!(recorder.calls, ["main", "double"]);
+        assert_eq!(
+            recorder.returns,
+            [
+                ("double".to_string(), vec![Value::Felt(42.into())]),
+                ("main".to_string(), vec![Value::Felt(42.into())]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_panic_data() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Panic = Struct<ut@core::panics::Panic>;
+                    type Marker = Struct<ut@Marker>;
+                    type Unit = Struct<ut@Tuple>;
+                    type Error = Struct<ut@Tuple, Panic, Array<felt252>>;
+                    type NotError = Struct<ut@Tuple, Marker, Array<felt252>>;
+                    type Result = Enum<ut@core::panics::PanicResult::<()>, Unit, Error>;
+                    type NotResult = Enum<ut@NotResult, Unit, NotError>;
+                "#,
+            )
+            .unwrap();
+        let registry = ProgramRegistry::new(&program).unwrap();
+        let err = |self_ty: usize| Value::Enum {
+            self_ty: program.type_declarations[self_ty].id.clone(),
+            index: 1,
+            payload: Box::new(Value::Struct(vec![
+                Value::Struct(Vec::new()),
+                Value::Array {
+                    ty: program.type_declarations[0].id.clone(),
+                    data: vec![Value::Felt(7.into())],
+                },
+            ])),
+        };
+
+        assert_eq!(panic_data(&registry, &err(7)), Some(vec![7.into()]));
+        // Same shape, but not a `PanicResult`.
+        assert_eq!(panic_data(&registry, &err(8)), None);
+        assert_eq!(panic_data(&registry, &Value::Felt(7.into())), None);
+    }
+}
@@ -0,0 +1,69 @@
+use cairo_lang_sierra::program::StatementIdx;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug},
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+/// Where the text printed by the `print` and `trace` libfuncs goes.
+#[derive(Clone, Default)]
+pub enum OutputSink {
+    /// Write it to the standard output, like the Cairo runner.
+    #[default]
+    Stdout,
+    /// Keep it in the [`VirtualMachine`](crate::VirtualMachine), to be retrieved with
+    /// [`VirtualMachine::take_output`](crate::VirtualMachine::take_output).
+    Capture,
+    Writer(Arc<Mutex<dyn Write + Send>>),
+    /// Discard it.
+    Ignore,
+}
+
+/// Text printed by a statement.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrintedOutput {
+    pub statement_idx: StatementIdx,
+    pub text: String,
+}
+
+impl OutputSink {
+    pub(crate) fn write(
+        &self,
+        captured: &mut Vec<PrintedOutput>,
+        statement_idx: StatementIdx,
+        text: String,
+    ) -> io::Result<()> {
+        match self {
+            Self::Stdout => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(text.as_bytes())?;
+                stdout.flush()
+            }
+            Self::Capture => {
+                captured.push(PrintedOutput {
+                    statement_idx,
+                    text,
+                });
+                Ok(())
+            }
+            Self::Writer(writer) => {
+                let mut writer = writer.lock().unwrap();
+                writer.write_all(text.as_bytes())?;
+                writer.flush()
+            }
+            Self::Ignore => Ok(()),
+        }
+    }
+}
+
+impl Debug for OutputSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stdout => f.write_str("Stdout"),
+            Self::Capture => f.write_str("Capture"),
+            Self::Writer(_) => f.write_str("Writer(..)"),
+            Self::Ignore => f.write_str("Ignore"),
+        }
+    }
+}
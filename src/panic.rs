@@ -0,0 +1,485 @@
+use crate::{
+    observer::{self, InvocationContext, StatementContext, VmObserver},
+    Value,
+};
+use cairo_lang_sierra::{ids::FunctionId, program::StatementIdx};
+use num_bigint::BigUint;
+use serde::Serialize;
+use starknet_types_core::felt::Felt;
+use std::{
+    fmt::{self, Display},
+    sync::LazyLock,
+};
+
+/// Marks the start of a serialized `ByteArray` in the panic data.
+static BYTE_ARRAY_MAGIC: LazyLock<Felt> = LazyLock::new(|| {
+    Felt::from_hex("0x46a6158a16a947e5916b2a2ca68501a45e93d7110e81aa2d6438b1c57c879a3").unwrap()
+});
+
+/// Number of bytes in each full word of a `ByteArray`.
+const BYTES_IN_WORD: usize = 31;
+
+/// A decoded element of the panic data.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum PanicItem {
+    /// A felt whose bytes are all printable ASCII characters.
+    ShortString(String),
+    /// A `ByteArray`, serialized after the magic prefix.
+    ByteArray(String),
+    Felt(Felt),
+}
+
+impl Display for PanicItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ShortString(value) | Self::ByteArray(value) => write!(f, "{value}"),
+            Self::Felt(value) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+/// Decode the felts of a panic into strings where possible.
+pub fn decode_panic_data(data: &[Felt]) -> Vec<PanicItem> {
+    let mut items = Vec::new();
+    let mut rest = data;
+    while let Some((first, tail)) = rest.split_first() {
+        if *first == *BYTE_ARRAY_MAGIC {
+            if let Some((value, tail)) = decode_byte_array(tail) {
+                items.push(PanicItem::ByteArray(value));
+                rest = tail;
+                continue;
+            }
+        }
+
+        items.push(match decode_short_string(first) {
+            Some(value) => PanicItem::ShortString(value),
+            None => PanicItem::Felt(*first),
+        });
+        rest = tail;
+    }
+
+    items
+}
+
+/// Render a panic as a message, joining its items with commas.
+pub fn format_panic_data(data: &[Felt]) -> String {
+    decode_panic_data(data)
+        .iter()
+        .map(PanicItem::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render the felts printed by the `print` libfunc like the Cairo runner: strings on their own
+/// line, and other values as hex along with their short string.
+pub fn format_for_debug(data: &[Felt]) -> String {
+    let items = decode_panic_data(data);
+    if let [PanicItem::ByteArray(value)] = items.as_slice() {
+        return value.clone();
+    }
+
+    items
+        .into_iter()
+        .map(|item| match item {
+            PanicItem::ByteArray(value) => format!("{value}\n"),
+            PanicItem::ShortString(value) => format!(
+                "[DEBUG]\t{}\n",
+                format_felt(&Felt::from_bytes_be_slice(value.as_bytes()))
+            ),
+            PanicItem::Felt(value) => format!("[DEBUG]\t{}\n", format_felt(&value)),
+        })
+        .collect()
+}
+
+/// Render a felt as hex, along with its short string if it is one.
+pub(crate) fn format_felt(value: &Felt) -> String {
+    match decode_short_string(value) {
+        Some(short_string) => format!("{value:#x} ('{short_string}')"),
+        None => format!("{value:#x}"),
+    }
+}
+
+/// Decode a serialized `ByteArray`: the number of full words, the words, the pending word and its
+/// length. Returns the remaining data.
+fn decode_byte_array(data: &[Felt]) -> Option<(String, &[Felt])> {
+    let (num_full_words, data) = data.split_first()?;
+    let num_full_words = usize::try_from(num_full_words.to_biguint()).ok()?;
+    if data.len() < num_full_words + 2 {
+        return None;
+    }
+    let (full_words, data) = data.split_at(num_full_words);
+    let [pending_word, pending_len, data @ ..] = data else {
+        return None;
+    };
+    let pending_len = usize::try_from(pending_len.to_biguint()).ok()?;
+    if pending_len >= BYTES_IN_WORD {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    for (word, len) in full_words
+        .iter()
+        .map(|word| (word, BYTES_IN_WORD))
+        .chain([(pending_word, pending_len)])
+    {
+        let word_bytes = word.to_bytes_be();
+        let (padding, word_bytes) = word_bytes.split_at(word_bytes.len() - len);
+        if padding.iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        bytes.extend_from_slice(word_bytes);
+    }
+
+    Some((String::from_utf8(bytes).ok()?, data))
+}
+
+fn decode_short_string(value: &Felt) -> Option<String> {
+    let bytes = BigUint::from_bytes_be(&value.to_bytes_be()).to_bytes_be();
+    if *value == Felt::ZERO || !bytes.iter().all(|byte| (0x20..0x7F).contains(byte)) {
+        return None;
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Observer that finds where panics originate.
+///
+/// It keeps track of the call stack and records it when a statement first constructs a panic, so
+/// the Sierra backtrace is available once the panic has unwound to the entry point.
+#[derive(Clone, Debug, Default)]
+pub struct PanicTracker {
+    stack: Vec<(FunctionId, StatementIdx)>,
+    origin: Vec<(FunctionId, StatementIdx)>,
+    panic_data: Option<Vec<Felt>>,
+    /// Whether the function about to return is returning a panic.
+    returns_panic: bool,
+}
+
+impl PanicTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The data of the panic returned by the entry point, if it panicked.
+    pub fn panic_data(&self) -> Option<&[Felt]> {
+        self.panic_data.as_deref()
+    }
+
+    /// The decoded panic message of the entry point, if it panicked.
+    pub fn message(&self) -> Option<String> {
+        self.panic_data().map(format_panic_data)
+    }
+
+    /// The call stack, innermost frame first, when the panic of the entry point was constructed.
+    pub fn backtrace(&self) -> Option<&[(FunctionId, StatementIdx)]> {
+        self.panic_data.as_ref().map(|_| self.origin.as_slice())
+    }
+}
+
+impl VmObserver for PanicTracker {
+    fn before_statement(&mut self, statement: &StatementContext) {
+        if let Some((_, statement_idx)) = self.stack.last_mut() {
+            *statement_idx = statement.statement_idx;
+        }
+    }
+
+    fn after_invocation(&mut self, invocation: &InvocationContext) {
+        // Every caller re-wraps the panic of its callee into its own `PanicResult`, which isn't
+        // where the panic comes from.
+        if !self.origin.is_empty() {
+            return;
+        }
+
+        let is_panic = |value: &Value| observer::panic_data(invocation.registry, value).is_some();
+        if invocation.results.iter().any(is_panic) && !invocation.args.iter().any(is_panic) {
+            self.origin = self.stack.iter().rev().cloned().collect();
+        }
+    }
+
+    fn on_function_call(&mut self, function_id: &FunctionId, _args: &[Value]) {
+        if self.stack.is_empty() {
+            self.origin.clear();
+            self.panic_data = None;
+        }
+        self.stack.push((function_id.clone(), StatementIdx(0)));
+    }
+
+    fn on_return(&mut self, _function_id: &FunctionId, _values: &[Value]) {
+        // A panic that didn't make it out of the frame that constructed it was handled there, so
+        // it isn't the one being tracked anymore.
+        if !std::mem::take(&mut self.returns_panic) && self.stack.len() <= self.origin.len() {
+            self.origin.clear();
+        }
+        self.stack.pop();
+    }
+
+    fn on_panic(&mut self, _function_id: &FunctionId, panic_data: &[Felt]) {
+        self.returns_panic = true;
+        if self.stack.len() == 1 {
+            self.panic_data = Some(panic_data.to_vec());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{starknet::StubSyscallHandler, VirtualMachine};
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_decode_panic_data() {
+        let short_string = Felt::from_bytes_be_slice(b"Invalid value");
+        let byte_array = [
+            *BYTE_ARRAY_MAGIC,
+            1.into(),
+            Felt::from_bytes_be_slice(b"This message is longer than 31 "),
+            Felt::from_bytes_be_slice(b"bytes"),
+            5.into(),
+        ];
+
+        assert_eq!(
+            decode_panic_data(&[short_string, 0xFF.into()]),
+            [
+                PanicItem::ShortString("Invalid value".to_string()),
+                PanicItem::Felt(0xFF.into()),
+            ]
+        );
+        assert_eq!(
+            format_panic_data(&byte_array),
+            "This message is longer than 31 bytes"
+        );
+
+        // Invalid UTF-8 falls back to raw felts.
+        let invalid = [*BYTE_ARRAY_MAGIC, 0.into(), 0xFF.into(), 1.into()];
+        assert_eq!(
+            decode_panic_data(&invalid)[1..],
+            [
+                PanicItem::Felt(0.into()),
+                PanicItem::Felt(0xFF.into()),
+                PanicItem::Felt(1.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_panic_backtrace() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Panic = Struct<ut@core::panics::Panic>;
+                    type Unit = Struct<ut@Tuple>;
+                    type Error = Struct<ut@Tuple, Panic, Array<felt252>>;
+                    type Result = Enum<ut@core::panics::PanicResult::<()>, Unit, Error>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc felt252_const<1651470189> = felt252_const<1651470189>;
+                    libfunc struct_construct<Panic> = struct_construct<Panic>;
+                    libfunc struct_construct<Error> = struct_construct<Error>;
+                    libfunc enum_init<Result, 1> = enum_init<Result, 1>;
+                    libfunc store_temp<Result> = store_temp<Result>;
+                    libfunc function_call<user@fail> = function_call<user@fail>;
+
+                    function_call<user@fail>() -> ([0]);
+                    return([0]);
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<1651470189>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    struct_construct<Panic>() -> ([2]);
+                    struct_construct<Error>([2], [0]) -> ([3]);
+                    enum_init<Result, 1>([3]) -> ([4]);
+                    store_temp<Result>([4]) -> ([4]);
+                    return([4]);
+
+                    main@0() -> (Result);
+                    fail@2() -> (Result);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let tracker = Arc::new(Mutex::new(PanicTracker::new()));
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.add_observer(tracker.clone());
+        vm.call_program(&program.funcs[0], 0, []);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let tracker = tracker.lock().unwrap();
+        assert_eq!(tracker.message().as_deref(), Some("boom"));
+        assert_eq!(
+            tracker.backtrace().unwrap(),
+            [
+                (program.funcs[1].id.clone(), StatementIdx(7)),
+                (program.funcs[0].id.clone(), StatementIdx(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrapped_panic_backtrace() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Panic = Struct<ut@core::panics::Panic>;
+                    type Unit = Struct<ut@Tuple>;
+                    type Wrapped = Struct<ut@Tuple, Unit>;
+                    type Wrapped2 = Struct<ut@Tuple, Wrapped>;
+                    type Error = Struct<ut@Tuple, Panic, Array<felt252>>;
+                    type Result = Enum<ut@core::panics::PanicResult::<()>, Unit, Error>;
+                    type Result2 = Enum<ut@core::panics::PanicResult::<((),)>, Wrapped, Error>;
+                    type Result3 = Enum<ut@core::panics::PanicResult::<(((),),)>, Wrapped2, Error>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc felt252_const<1651470189> = felt252_const<1651470189>;
+                    libfunc branch_align = branch_align;
+                    libfunc struct_construct<Panic> = struct_construct<Panic>;
+                    libfunc struct_construct<Error> = struct_construct<Error>;
+                    libfunc struct_construct<Wrapped> = struct_construct<Wrapped>;
+                    libfunc struct_construct<Wrapped2> = struct_construct<Wrapped2>;
+                    libfunc enum_init<Result, 1> = enum_init<Result, 1>;
+                    libfunc enum_init<Result2, 0> = enum_init<Result2, 0>;
+                    libfunc enum_init<Result2, 1> = enum_init<Result2, 1>;
+                    libfunc enum_init<Result3, 0> = enum_init<Result3, 0>;
+                    libfunc enum_init<Result3, 1> = enum_init<Result3, 1>;
+                    libfunc enum_match<Result> = enum_match<Result>;
+                    libfunc enum_match<Result2> = enum_match<Result2>;
+                    libfunc store_temp<Result> = store_temp<Result>;
+                    libfunc store_temp<Result2> = store_temp<Result2>;
+                    libfunc store_temp<Result3> = store_temp<Result3>;
+                    libfunc function_call<user@middle> = function_call<user@middle>;
+                    libfunc function_call<user@fail> = function_call<user@fail>;
+
+                    function_call<user@middle>() -> ([0]);
+                    enum_match<Result2>([0]) { fallthrough([1]) 7([2]) };
+                    branch_align() -> ();
+                    struct_construct<Wrapped2>([1]) -> ([3]);
+                    enum_init<Result3, 0>([3]) -> ([3]);
+                    store_temp<Result3>([3]) -> ([3]);
+                    return([3]);
+                    branch_align() -> ();
+                    enum_init<Result3, 1>([2]) -> ([4]);
+                    store_temp<Result3>([4]) -> ([4]);
+                    return([4]);
+                    function_call<user@fail>() -> ([0]);
+                    enum_match<Result>([0]) { fallthrough([1]) 18([2]) };
+                    branch_align() -> ();
+                    struct_construct<Wrapped>([1]) -> ([3]);
+                    enum_init<Result2, 0>([3]) -> ([3]);
+                    store_temp<Result2>([3]) -> ([3]);
+                    return([3]);
+                    branch_align() -> ();
+                    enum_init<Result2, 1>([2]) -> ([4]);
+                    store_temp<Result2>([4]) -> ([4]);
+                    return([4]);
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<1651470189>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    struct_construct<Panic>() -> ([2]);
+                    struct_construct<Error>([2], [0]) -> ([3]);
+                    enum_init<Result, 1>([3]) -> ([4]);
+                    store_temp<Result>([4]) -> ([4]);
+                    return([4]);
+
+                    main@0() -> (Result3);
+                    middle@11() -> (Result2);
+                    fail@22() -> (Result);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let tracker = Arc::new(Mutex::new(PanicTracker::new()));
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.add_observer(tracker.clone());
+        vm.call_program(&program.funcs[0], 0, []);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let tracker = tracker.lock().unwrap();
+        assert_eq!(tracker.message().as_deref(), Some("boom"));
+        assert_eq!(
+            tracker.backtrace().unwrap(),
+            [
+                (program.funcs[2].id.clone(), StatementIdx(27)),
+                (program.funcs[1].id.clone(), StatementIdx(11)),
+                (program.funcs[0].id.clone(), StatementIdx(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handled_panic_backtrace() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Panic = Struct<ut@core::panics::Panic>;
+                    type Unit = Struct<ut@Tuple>;
+                    type Error = Struct<ut@Tuple, Panic, Array<felt252>>;
+                    type Result = Enum<ut@core::panics::PanicResult::<()>, Unit, Error>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc felt252_const<1651470189> = felt252_const<1651470189>;
+                    libfunc struct_construct<Panic> = struct_construct<Panic>;
+                    libfunc struct_construct<Error> = struct_construct<Error>;
+                    libfunc struct_construct<Unit> = struct_construct<Unit>;
+                    libfunc enum_init<Result, 1> = enum_init<Result, 1>;
+                    libfunc drop<Result> = drop<Result>;
+                    libfunc drop<Unit> = drop<Unit>;
+                    libfunc store_temp<Result> = store_temp<Result>;
+                    libfunc function_call<user@handled> = function_call<user@handled>;
+                    libfunc function_call<user@fail> = function_call<user@fail>;
+
+                    function_call<user@handled>() -> ([0]);
+                    drop<Unit>([0]) -> ();
+                    function_call<user@fail>() -> ([1]);
+                    return([1]);
+                    array_new<felt252>() -> ([0]);
+                    struct_construct<Panic>() -> ([1]);
+                    struct_construct<Error>([1], [0]) -> ([2]);
+                    enum_init<Result, 1>([2]) -> ([3]);
+                    drop<Result>([3]) -> ();
+                    struct_construct<Unit>() -> ([4]);
+                    return([4]);
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<1651470189>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    struct_construct<Panic>() -> ([2]);
+                    struct_construct<Error>([2], [0]) -> ([3]);
+                    enum_init<Result, 1>([3]) -> ([4]);
+                    store_temp<Result>([4]) -> ([4]);
+                    return([4]);
+
+                    main@0() -> (Result);
+                    handled@4() -> (Unit);
+                    fail@11() -> (Result);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let tracker = Arc::new(Mutex::new(PanicTracker::new()));
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.add_observer(tracker.clone());
+        vm.call_program(&program.funcs[0], 0, []);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let tracker = tracker.lock().unwrap();
+        assert_eq!(tracker.message().as_deref(), Some("boom"));
+        assert_eq!(
+            tracker.backtrace().unwrap(),
+            [
+                (program.funcs[2].id.clone(), StatementIdx(16)),
+                (program.funcs[0].id.clone(), StatementIdx(2)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use crate::source_map::SourceMap;
+use cairo_lang_sierra::{ids::FunctionId, program::StatementIdx};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Write},
+    ops::AddAssign,
+    sync::Arc,
+};
+
+pub use self::gas::{FunctionGas, GasProfiler, GasReport};
+
+mod gas;
+
+/// Step counting profiler.
+///
+/// When enabled on a [`VirtualMachine`](crate::VirtualMachine), every executed statement is
+/// attributed to its statement index, its libfunc and the functions in the call stack.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    pub statements: HashMap<StatementIdx, u64>,
+    pub libfuncs: HashMap<&'static str, u64>,
+    pub functions: HashMap<FunctionId, FunctionProfile>,
+    /// Exclusive steps of every distinct call stack, outermost function first.
+    pub stacks: HashMap<Vec<FunctionId>, u64>,
+    /// Used to show the Cairo code location of the statements.
+    pub source_map: Option<Arc<SourceMap>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FunctionProfile {
+    /// Number of times the function has been entered.
+    pub calls: u64,
+    /// Steps executed by the function itself.
+    pub exclusive_steps: u64,
+    /// Steps executed by the function and all of its callees.
+    pub inclusive_steps: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source_map(mut self, source_map: Option<Arc<SourceMap>>) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Record the execution of a statement.
+    ///
+    /// The call stack is ordered from the outermost frame to the one executing the statement.
+    pub fn record_step(
+        &mut self,
+        statement_idx: StatementIdx,
+        libfunc: Option<&'static str>,
+        call_stack: &[FunctionId],
+    ) {
+        *self.statements.entry(statement_idx).or_default() += 1;
+        if let Some(libfunc) = libfunc {
+            *self.libfuncs.entry(libfunc).or_default() += 1;
+        }
+
+        attribute(&mut self.functions, &mut self.stacks, call_stack, 1);
+    }
+
+    /// Record a new frame for a function.
+    pub fn record_call(&mut self, function_id: &FunctionId) {
+        self.functions.entry(function_id.clone()).or_default().calls += 1;
+    }
+
+    /// Total number of executed statements.
+    pub fn total_steps(&self) -> u64 {
+        self.statements.values().sum()
+    }
+
+    /// Render the profile in the folded stacks format used by flamegraph tools.
+    pub fn folded_stacks(&self) -> String {
+        fold_stacks(self.stacks.iter().map(|(stack, steps)| (stack, *steps)))
+    }
+}
+
+/// Totals of a function that a profiler attributes amounts to.
+trait FunctionTotals: Default {
+    type Amount: AddAssign + Copy;
+
+    /// Add an amount spent by the function itself.
+    fn add_exclusive(&mut self, amount: Self::Amount);
+
+    /// Add an amount spent by the function or one of its callees.
+    fn add_inclusive(&mut self, amount: Self::Amount);
+}
+
+impl FunctionTotals for FunctionProfile {
+    type Amount = u64;
+
+    fn add_exclusive(&mut self, amount: u64) {
+        self.exclusive_steps += amount;
+    }
+
+    fn add_inclusive(&mut self, amount: u64) {
+        self.inclusive_steps += amount;
+    }
+}
+
+/// Attribute an amount spent by the innermost function of the call stack to that function, to its
+/// callers as inclusive and to the stack itself. The call stack is ordered from the outermost
+/// frame.
+fn attribute<T: FunctionTotals>(
+    functions: &mut HashMap<FunctionId, T>,
+    stacks: &mut HashMap<Vec<FunctionId>, T::Amount>,
+    call_stack: &[FunctionId],
+    amount: T::Amount,
+) {
+    let Some((current, callers)) = call_stack.split_last() else {
+        return;
+    };
+
+    let totals = functions.entry(current.clone()).or_default();
+    totals.add_exclusive(amount);
+    totals.add_inclusive(amount);
+    // Recursive functions appear multiple times in the stack, but an amount is only counted once.
+    for (i, function_id) in callers.iter().enumerate() {
+        if function_id != current && !callers[..i].contains(function_id) {
+            functions
+                .entry(function_id.clone())
+                .or_default()
+                .add_inclusive(amount);
+        }
+    }
+
+    match stacks.get_mut(call_stack) {
+        Some(total) => *total += amount,
+        None => {
+            stacks.insert(call_stack.to_vec(), amount);
+        }
+    }
+}
+
+fn fold_stacks<'a>(stacks: impl Iterator<Item = (&'a Vec<FunctionId>, u64)>) -> String {
+    let stacks = stacks
+        .map(|(stack, weight)| {
+            let names = stack
+                .iter()
+                .map(|id| id.to_string().replace(';', ":"))
+                .collect::<Vec<_>>();
+            (names.join(";"), weight)
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    stacks
+        .into_iter()
+        .fold(String::new(), |mut output, (stack, weight)| {
+            writeln!(output, "{stack} {weight}").unwrap();
+            output
+        })
+}
+
+impl Display for Profiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total_steps = self.total_steps();
+        writeln!(f, "Total steps: {total_steps}")?;
+
+        let mut functions = self.functions.iter().collect::<Vec<_>>();
+        functions.sort_by(|(lhs_id, lhs), (rhs_id, rhs)| {
+            (rhs.inclusive_steps, rhs.exclusive_steps)
+                .cmp(&(lhs.inclusive_steps, lhs.exclusive_steps))
+                .then_with(|| lhs_id.id.cmp(&rhs_id.id))
+        });
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>10} {:>10} {:>10}  function",
+            "calls", "inclusive", "exclusive"
+        )?;
+        for (function_id, profile) in functions {
+            writeln!(
+                f,
+                "{:>10} {:>10} {:>10}  {function_id}",
+                profile.calls, profile.inclusive_steps, profile.exclusive_steps
+            )?;
+        }
+
+        let mut libfuncs = self.libfuncs.iter().collect::<Vec<_>>();
+        libfuncs.sort_by(|(lhs_name, lhs), (rhs_name, rhs)| {
+            rhs.cmp(lhs).then_with(|| lhs_name.cmp(rhs_name))
+        });
+        writeln!(f)?;
+        writeln!(f, "{:>10}  libfunc", "steps")?;
+        for (name, steps) in libfuncs {
+            writeln!(f, "{steps:>10}  {name}")?;
+        }
+
+        let mut statements = self.statements.iter().collect::<Vec<_>>();
+        statements.sort_by(|(lhs_idx, lhs), (rhs_idx, rhs)| {
+            rhs.cmp(lhs).then_with(|| lhs_idx.cmp(rhs_idx))
+        });
+        writeln!(f)?;
+        writeln!(f, "{:>10}  statement", "steps")?;
+        for (statement_idx, steps) in statements {
+            write!(f, "{steps:>10}  {}", statement_idx.0)?;
+            match self
+                .source_map
+                .as_ref()
+                .and_then(|map| map.location(*statement_idx))
+            {
+                Some(location) => writeln!(f, "  {location}")?,
+                None => writeln!(f)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{starknet::StubSyscallHandler, Value, VirtualMachine};
+    use cairo_lang_sierra::ProgramParser;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_profile_function_call() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@double> = function_call<user@double>;
+
+                    function_call<user@double>([0]) -> ([1]);
+                    store_temp<felt252>([1]) -> ([1]);
+                    return([1]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (felt252);
+                    double@3([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.enable_profiler();
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let profiler = vm.profiler().unwrap();
+        assert_eq!(profiler.total_steps(), 7);
+        assert_eq!(profiler.libfuncs["store_temp"], 2);
+
+        let main = &profiler.functions[&program.funcs[0].id];
+        assert_eq!(
+            (main.calls, main.inclusive_steps, main.exclusive_steps),
+            (1, 7, 3)
+        );
+        let double = &profiler.functions[&program.funcs[1].id];
+        assert_eq!(
+            (double.calls, double.inclusive_steps, double.exclusive_steps),
+            (1, 4, 4)
+        );
+
+        assert_eq!(profiler.folded_stacks(), "main 3\nmain;double 4\n");
+    }
+}
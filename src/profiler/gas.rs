@@ -0,0 +1,278 @@
+use super::{attribute, fold_stacks, FunctionTotals};
+use crate::source_map::SourceMap;
+use cairo_lang_sierra::{ids::FunctionId, program::StatementIdx};
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+
+/// Gas profiler.
+///
+/// Attributes the gas consumed by each statement to the statement itself and to the functions in
+/// the call stack. A statement consumes the cost the gas metadata assigns to the branch it takes,
+/// plus whatever it takes from the gas counter without moving it into the wallet of the function,
+/// so the gas shows up where it is spent rather than where `withdraw_gas` withdraws it.
+#[derive(Clone, Debug, Default)]
+pub struct GasProfiler {
+    pub statements: HashMap<StatementIdx, i128>,
+    pub functions: HashMap<FunctionId, FunctionGas>,
+    /// Exclusive gas of every distinct call stack, outermost function first.
+    pub stacks: HashMap<Vec<FunctionId>, i128>,
+    /// Used to add the Cairo code location of the statements to the report.
+    pub source_map: Option<Arc<SourceMap>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FunctionGas {
+    /// Gas consumed by the function itself.
+    pub exclusive_gas: i128,
+    /// Gas consumed by the function and all of its callees.
+    pub inclusive_gas: i128,
+}
+
+impl FunctionTotals for FunctionGas {
+    type Amount = i128;
+
+    fn add_exclusive(&mut self, amount: i128) {
+        self.exclusive_gas += amount;
+    }
+
+    fn add_inclusive(&mut self, amount: i128) {
+        self.inclusive_gas += amount;
+    }
+}
+
+/// Serializable summary of a [`GasProfiler`], sorted by consumption.
+#[derive(Clone, Debug, Serialize)]
+pub struct GasReport {
+    pub total_gas: i128,
+    pub functions: Vec<FunctionGasReport>,
+    pub statements: Vec<StatementGasReport>,
+    /// Gas of the statements grouped by Cairo code location, when the program has them.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<LocationGasReport>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FunctionGasReport {
+    pub id: u64,
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub gas: FunctionGas,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StatementGasReport {
+    pub statement_idx: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    pub gas: i128,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LocationGasReport {
+    pub location: String,
+    pub gas: i128,
+}
+
+impl GasProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_source_map(mut self, source_map: Option<Arc<SourceMap>>) -> Self {
+        self.source_map = source_map;
+        self
+    }
+
+    /// Record the gas consumed by a statement.
+    ///
+    /// The call stack is ordered from the outermost frame to the one executing the statement.
+    pub fn record_gas(
+        &mut self,
+        statement_idx: StatementIdx,
+        call_stack: &[FunctionId],
+        consumed: i128,
+    ) {
+        if consumed == 0 {
+            return;
+        }
+
+        *self.statements.entry(statement_idx).or_default() += consumed;
+
+        attribute(&mut self.functions, &mut self.stacks, call_stack, consumed);
+    }
+
+    /// Total gas consumed by the run.
+    pub fn total_gas(&self) -> i128 {
+        self.statements.values().sum()
+    }
+
+    pub fn report(&self) -> GasReport {
+        let mut functions = self
+            .functions
+            .iter()
+            .map(|(id, gas)| FunctionGasReport {
+                id: id.id,
+                name: id.debug_name.as_ref().map(|name| name.to_string()),
+                gas: *gas,
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|lhs, rhs| {
+            rhs.gas
+                .inclusive_gas
+                .cmp(&lhs.gas.inclusive_gas)
+                .then_with(|| lhs.id.cmp(&rhs.id))
+        });
+
+        let mut statements = self
+            .statements
+            .iter()
+            .map(|(statement_idx, gas)| StatementGasReport {
+                statement_idx: statement_idx.0,
+                location: self
+                    .source_map
+                    .as_ref()
+                    .and_then(|map| map.location(*statement_idx))
+                    .map(|location| location.to_string()),
+                gas: *gas,
+            })
+            .collect::<Vec<_>>();
+        statements.sort_by(|lhs, rhs| {
+            rhs.gas
+                .cmp(&lhs.gas)
+                .then_with(|| lhs.statement_idx.cmp(&rhs.statement_idx))
+        });
+
+        let mut locations = HashMap::<_, i128>::new();
+        for statement in &statements {
+            if let Some(location) = &statement.location {
+                *locations.entry(location.clone()).or_default() += statement.gas;
+            }
+        }
+        let mut locations = locations
+            .into_iter()
+            .map(|(location, gas)| LocationGasReport { location, gas })
+            .collect::<Vec<_>>();
+        locations.sort_by(|lhs, rhs| {
+            rhs.gas
+                .cmp(&lhs.gas)
+                .then_with(|| lhs.location.cmp(&rhs.location))
+        });
+
+        GasReport {
+            total_gas: self.total_gas(),
+            functions,
+            statements,
+            locations,
+        }
+    }
+
+    /// Render the profile in the folded stacks format used by flamegraph tools.
+    ///
+    /// Stacks with a net refund are omitted since flamegraphs can't represent negative weights.
+    pub fn folded_stacks(&self) -> String {
+        fold_stacks(
+            self.stacks
+                .iter()
+                .filter_map(|(stack, gas)| Some((stack, u64::try_from(*gas).ok()?))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        source_map::{SourceLocation, SourceMap, SourcePosition},
+        starknet::StubSyscallHandler,
+        Value, VirtualMachine,
+    };
+    use cairo_lang_sierra::{extensions::gas::CostTokenType, program::StatementIdx, ProgramParser};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_gas_profile_function_call() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type GasBuiltin = GasBuiltin;
+
+                    libfunc withdraw_gas = withdraw_gas;
+                    libfunc branch_align = branch_align;
+                    libfunc store_temp<RangeCheck> = store_temp<RangeCheck>;
+                    libfunc store_temp<GasBuiltin> = store_temp<GasBuiltin>;
+                    libfunc function_call<user@charge> = function_call<user@charge>;
+
+                    function_call<user@charge>([0], [1]) -> ([2], [3]);
+                    return([2], [3]);
+                    withdraw_gas([0], [1]) { fallthrough([2], [3]) 8([4], [5]) };
+                    branch_align() -> ();
+                    store_temp<RangeCheck>([2]) -> ([2]);
+                    store_temp<GasBuiltin>([3]) -> ([3]);
+                    function_call<user@charge>([2], [3]) -> ([6], [7]);
+                    return([6], [7]);
+                    branch_align() -> ();
+                    store_temp<RangeCheck>([4]) -> ([4]);
+                    store_temp<GasBuiltin>([5]) -> ([5]);
+                    return([4], [5]);
+
+                    main@0([0]: RangeCheck, [1]: GasBuiltin) -> (RangeCheck, GasBuiltin);
+                    charge@2([0]: RangeCheck, [1]: GasBuiltin) -> (RangeCheck, GasBuiltin);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let location = SourceLocation {
+            file: "lib.cairo".to_string(),
+            start: SourcePosition { line: 3, col: 4 },
+            end: SourcePosition { line: 3, col: 10 },
+            in_macro: false,
+        };
+        let mut source_map = SourceMap::new();
+        source_map.insert(StatementIdx(4), location.clone());
+        source_map.insert(StatementIdx(5), location.clone());
+
+        let initial_gas = 10000;
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.set_source_map(source_map);
+        vm.enable_gas_profiler();
+        vm.call_program(&program.funcs[0], initial_gas, Vec::<Value>::new());
+        let trace = vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        let Some(Value::U64(remaining_gas)) = trace.states.last().unwrap().items.get(&3) else {
+            panic!("no remaining gas");
+        };
+
+        // The cost of `main` itself is pre-charged by the runner, not taken from the counter.
+        let main_cost = vm.gas.initial_wallet(&program.funcs[0].id)[&CostTokenType::Const] as i128;
+        let consumed = (initial_gas - remaining_gas) as i128 + main_cost;
+        let gas_profiler = vm.gas_profiler().unwrap();
+        assert_eq!(gas_profiler.total_gas(), consumed);
+
+        // `charge` recurses 12 times before running out of gas. A step costs 100 gas and a range
+        // check 70.
+        let statements = |idx| gas_profiler.statements[&StatementIdx(idx)];
+        assert_eq!(statements(0), 200);
+        assert_eq!(statements(2), 12 * 370 + 470);
+        assert_eq!(statements(4), 12 * 100);
+        assert_eq!(statements(5), 12 * 100);
+        assert_eq!(statements(6), 12 * 200);
+        assert_eq!(statements(9), 100);
+        assert_eq!(statements(10), 100);
+        assert_eq!(gas_profiler.statements.len(), 7);
+
+        let main = &gas_profiler.functions[&program.funcs[0].id];
+        assert_eq!((main.inclusive_gas, main.exclusive_gas), (consumed, 200));
+        let charge = &gas_profiler.functions[&program.funcs[1].id];
+        assert_eq!(
+            (charge.inclusive_gas, charge.exclusive_gas),
+            (consumed - 200, consumed - 200)
+        );
+
+        let report = gas_profiler.report();
+        assert_eq!(report.statements[0].statement_idx, 2);
+        assert_eq!(report.locations.len(), 1);
+        assert_eq!(report.locations[0].location, location.to_string());
+        assert_eq!(report.locations[0].gas, 2 * 12 * 100);
+    }
+}
@@ -0,0 +1,184 @@
+use cairo_lang_sierra::program::StatementIdx;
+use sierra_emu::{
+    debugger::{render_value, Breakpoint, Debugger, StopReason},
+    starknet::StubSyscallHandler,
+    watch::{WatchCondition, Watchpoint},
+    Value,
+};
+use starknet_types_core::felt::Felt;
+use std::io::{stdin, stdout, BufRead, Write};
+
+const HELP: &str = "\
+Commands:
+  s, step                  execute one statement, entering function calls
+  n, next                  execute one statement, stepping over function calls
+  f, finish                run until the current function returns
+  rs, reverse-step         undo the last executed statement
+  c, continue              run until a breakpoint is hit or the program ends
+  b, break <idx>           break at a statement index
+  b, break fn <name>       break when entering a function
+  b, break libfunc <name>  break before invoking a libfunc
+  w, watch <felt>          stop when a statement produces a felt252 value
+  d, delete <n>            delete a breakpoint
+  i, info breakpoints      list the breakpoints
+  i, info watchpoints      list the watchpoints
+  i, info locals           list the variables of the current frame
+  p, print <var>           print a variable of the current frame
+  bt, backtrace            print the call stack
+  l, list                  show the current statement
+  q, quit                  exit the debugger
+An empty line repeats the previous command.";
+
+/// Run an interactive debugging session on stdin/stdout.
+pub fn run(mut debugger: Debugger) -> Result<(), Box<dyn std::error::Error>> {
+    let syscall_handler = &mut StubSyscallHandler::default();
+    let mut last_command = String::new();
+
+    println!("Type `help` for a list of commands.");
+    show_current(&debugger);
+
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("(sierra-emu) ");
+        stdout().flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+        let line = match line.trim() {
+            "" => last_command.clone(),
+            line => line.to_string(),
+        };
+        last_command.clone_from(&line);
+
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let rest = words.collect::<Vec<_>>();
+
+        match (command, rest.as_slice()) {
+            ("s" | "step", []) => {
+                let reason = debugger.step(syscall_handler);
+                stop(&debugger, reason);
+            }
+            ("n" | "next", []) => {
+                let reason = debugger.next(syscall_handler);
+                stop(&debugger, reason);
+            }
+            ("f" | "finish", []) => {
+                let reason = debugger.finish(syscall_handler);
+                stop(&debugger, reason);
+            }
+            ("rs" | "reverse-step", []) => match debugger.step_back(syscall_handler) {
+                true => show_current(&debugger),
+                false => println!("No statement to undo."),
+            },
+            ("c" | "continue", []) => {
+                let reason = debugger.resume(syscall_handler);
+                stop(&debugger, reason);
+            }
+            ("b" | "break", [statement_idx]) => match statement_idx.parse() {
+                Ok(statement_idx) => add_breakpoint(
+                    &mut debugger,
+                    Breakpoint::Statement(StatementIdx(statement_idx)),
+                ),
+                Err(_) => println!("Invalid statement index `{statement_idx}`."),
+            },
+            ("b" | "break", ["fn", name]) => {
+                add_breakpoint(&mut debugger, Breakpoint::Function(name.to_string()))
+            }
+            ("b" | "break", ["libfunc", name]) => {
+                add_breakpoint(&mut debugger, Breakpoint::Libfunc(name.to_string()))
+            }
+            ("w" | "watch", [value]) => match value.parse::<Felt>() {
+                Ok(value) => println!(
+                    "Watchpoint #{} set.",
+                    debugger
+                        .vm
+                        .add_watchpoint(Watchpoint::new(WatchCondition::Value(Value::Felt(value))))
+                ),
+                Err(_) => println!("Invalid felt252 `{value}`."),
+            },
+            ("d" | "delete", [index]) => {
+                match index
+                    .parse()
+                    .ok()
+                    .and_then(|i| debugger.remove_breakpoint(i))
+                {
+                    Some(breakpoint) => println!("Deleted {breakpoint:?}."),
+                    None => println!("No breakpoint `{index}`."),
+                }
+            }
+            ("i" | "info", ["breakpoints" | "b"]) => {
+                for (i, breakpoint) in debugger.breakpoints().iter().enumerate() {
+                    println!("#{i}: {breakpoint:?}");
+                }
+            }
+            ("i" | "info", ["watchpoints" | "w"]) => {
+                for (i, watchpoint) in debugger.vm.watchpoints().iter().enumerate() {
+                    println!("#{i}: {watchpoint:?}");
+                }
+            }
+            ("i" | "info", ["locals"]) => {
+                for (id, ty, value) in debugger.variables() {
+                    match ty {
+                        Some(ty) => println!(
+                            "{id}: {ty} = {}",
+                            render_value(debugger.vm.registry(), &ty, &value)
+                        ),
+                        None => println!("{id}: {value:?}"),
+                    }
+                }
+            }
+            ("p" | "print", [name]) => match debugger.print(name) {
+                Some(value) => println!("{value}"),
+                None => println!("No variable `{name}` in the current frame."),
+            },
+            ("bt" | "backtrace", []) => {
+                for (i, frame) in debugger.backtrace().iter().enumerate() {
+                    println!("#{i} {frame}");
+                }
+            }
+            ("l" | "list", []) => show_current(&debugger),
+            ("q" | "quit", []) => break,
+            ("h" | "help", []) => println!("{HELP}"),
+            _ => println!("Unknown command `{line}`. Type `help` for a list of commands."),
+        }
+    }
+
+    Ok(())
+}
+
+fn add_breakpoint(debugger: &mut Debugger, breakpoint: Breakpoint) {
+    println!("Breakpoint #{} set.", debugger.add_breakpoint(breakpoint));
+}
+
+fn stop(debugger: &Debugger, reason: StopReason) {
+    match reason {
+        StopReason::Step => {}
+        StopReason::Breakpoint(index) => println!("Hit breakpoint #{index}."),
+        StopReason::Watchpoint(index) => println!("Hit watchpoint #{index}."),
+        StopReason::Finished => {
+            match debugger.return_values() {
+                Some(values) => println!("Program finished, returning {values:?}."),
+                None => println!("Program finished."),
+            }
+            return;
+        }
+    }
+    show_current(debugger);
+}
+
+fn show_current(debugger: &Debugger) {
+    let Some(frame) = debugger.backtrace().into_iter().next() else {
+        println!("The program is not running.");
+        return;
+    };
+
+    println!("{frame}");
+    println!(
+        "    {}",
+        debugger.vm.program.statements[frame.statement_idx.0]
+    );
+}
@@ -0,0 +1,327 @@
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        array::ArrayConcreteLibfunc,
+        bounded_int::{BoundedIntConcreteLibfunc, BoundedIntDivRemAlgorithm},
+        bytes31::Bytes31ConcreteLibfunc,
+        casts::{CastConcreteLibfunc, CastType},
+        circuit::{CircuitConcreteLibfunc, CircuitInfo, CircuitTypeConcrete},
+        core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+        ec::EcConcreteLibfunc,
+        felt252_dict::Felt252DictConcreteLibfunc,
+        gas::GasConcreteLibfunc,
+        int::{
+            signed::{SintConcrete, SintTraits},
+            signed128::Sint128Concrete,
+            unsigned::{UintConcrete, UintTraits},
+            unsigned128::Uint128Concrete,
+            unsigned256::Uint256Concrete,
+            unsigned512::Uint512Concrete,
+            IntMulTraits,
+        },
+        is_zero::IsZeroTraits,
+        pedersen::PedersenConcreteLibfunc,
+        poseidon::PoseidonConcreteLibfunc,
+        qm31::QM31Concrete,
+        range::IntRangeConcreteLibfunc,
+        starknet::StarknetConcreteLibfunc,
+    },
+    ids::ConcreteTypeId,
+    program_registry::ProgramRegistry,
+};
+use cairo_lang_sierra_gas::core_libfunc_cost::{
+    DICT_SQUASH_FIXED_COST, DICT_SQUASH_REPEATED_ACCESS_COST, DICT_SQUASH_UNIQUE_KEY_COST,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Resources used by an execution.
+///
+/// Builtin instances are counted the same way the CASM lowering of every libfunc uses them, so
+/// they can be compared with the resources reported by the CASM runner. Steps are Sierra
+/// statements, not CASM instructions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionResources {
+    pub n_steps: u64,
+    pub range_check: u64,
+    pub range_check96: u64,
+    pub pedersen: u64,
+    pub poseidon: u64,
+    pub bitwise: u64,
+    pub ec_op: u64,
+    pub add_mod: u64,
+    pub mul_mod: u64,
+    pub segment_arena: u64,
+}
+
+impl ExecutionResources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The used builtins, keyed by the names used by the CASM runner.
+    pub fn builtin_instance_counter(&self) -> BTreeMap<&'static str, u64> {
+        [
+            ("range_check_builtin", self.range_check),
+            ("range_check96_builtin", self.range_check96),
+            ("pedersen_builtin", self.pedersen),
+            ("poseidon_builtin", self.poseidon),
+            ("bitwise_builtin", self.bitwise),
+            ("ec_op_builtin", self.ec_op),
+            ("add_mod_builtin", self.add_mod),
+            ("mul_mod_builtin", self.mul_mod),
+            ("segment_arena_builtin", self.segment_arena),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count != 0)
+        .collect()
+    }
+
+    /// Whether [`ExecutionResources::record_libfunc`] needs the arguments of the libfunc. It's
+    /// given an empty slice otherwise.
+    pub fn reads_args(libfunc: &CoreConcreteLibfunc) -> bool {
+        matches!(
+            libfunc,
+            CoreConcreteLibfunc::Felt252Dict(Felt252DictConcreteLibfunc::Squash(_))
+        )
+    }
+
+    /// Record the builtins used by a libfunc which has been invoked with `args` and taken the
+    /// branch `branch_idx`.
+    pub fn record_libfunc(
+        &mut self,
+        registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+        libfunc: &CoreConcreteLibfunc,
+        branch_idx: usize,
+        args: &[Value],
+    ) {
+        match libfunc {
+            CoreConcreteLibfunc::Array(
+                ArrayConcreteLibfunc::SnapshotMultiPopFront(_)
+                | ArrayConcreteLibfunc::SnapshotMultiPopBack(_)
+                | ArrayConcreteLibfunc::Get(_)
+                | ArrayConcreteLibfunc::Slice(_),
+            ) => self.range_check += 1,
+            CoreConcreteLibfunc::BoundedInt(libfunc) => match libfunc {
+                BoundedIntConcreteLibfunc::DivRem(info) => {
+                    self.range_check +=
+                        match BoundedIntDivRemAlgorithm::try_new(&info.lhs, &info.rhs).unwrap() {
+                            BoundedIntDivRemAlgorithm::KnownSmallRhs => 3,
+                            BoundedIntDivRemAlgorithm::KnownSmallQuotient { .. }
+                            | BoundedIntDivRemAlgorithm::KnownSmallLhs { .. } => 4,
+                        }
+                }
+                BoundedIntConcreteLibfunc::Constrain(_) => self.range_check += 1,
+                _ => {}
+            },
+            CoreConcreteLibfunc::Bytes31(Bytes31ConcreteLibfunc::TryFromFelt252(_)) => {
+                self.range_check += 3
+            }
+            CoreConcreteLibfunc::Cast(CastConcreteLibfunc::Downcast(info)) => {
+                self.range_check += if info.from_range.is_full_felt252_range() {
+                    [2, 3][branch_idx]
+                } else {
+                    match info.cast_type() {
+                        CastType {
+                            overflow_above: false,
+                            overflow_below: false,
+                        } => 0,
+                        CastType {
+                            overflow_above: true,
+                            overflow_below: true,
+                        } => [2, 1][branch_idx],
+                        _ => 1,
+                    }
+                }
+            }
+            CoreConcreteLibfunc::Circuit(libfunc) => match libfunc {
+                CircuitConcreteLibfunc::Eval(info) => {
+                    let circuit_info = circuit_info(registry, &info.ty);
+                    self.add_mod += circuit_info.add_offsets.len() as u64;
+                    self.mul_mod += circuit_info.mul_offsets.len() as u64;
+                }
+                CircuitConcreteLibfunc::InitCircuitData(info) => {
+                    self.range_check96 += circuit_info(registry, &info.ty).rc96_usage() as u64;
+                }
+                CircuitConcreteLibfunc::U96GuaranteeVerify(_) => self.range_check96 += 1,
+                CircuitConcreteLibfunc::FailureGuaranteeVerify(_) => self.range_check96 += 6,
+                _ => {}
+            },
+            CoreConcreteLibfunc::Ec(libfunc) => match libfunc {
+                EcConcreteLibfunc::StateAddMul(_) => self.ec_op += 1,
+                EcConcreteLibfunc::PointFromX(_) if branch_idx == 0 => self.range_check += 3,
+                _ => {}
+            },
+            CoreConcreteLibfunc::Felt252Dict(libfunc) => match libfunc {
+                Felt252DictConcreteLibfunc::New(_) => self.segment_arena += 1,
+                Felt252DictConcreteLibfunc::Squash(_) => {
+                    let Some(Value::FeltDict { data, log, .. }) = args.get(3) else {
+                        panic!()
+                    };
+                    let unique_keys = data.len() as u64;
+                    let repeated_accesses = (log.len() as u64).saturating_sub(unique_keys);
+
+                    self.range_check += DICT_SQUASH_FIXED_COST.range_checks as u64
+                        + unique_keys * DICT_SQUASH_UNIQUE_KEY_COST.range_checks as u64
+                        + repeated_accesses * DICT_SQUASH_REPEATED_ACCESS_COST.range_checks as u64;
+                    self.segment_arena += 1;
+                }
+            },
+            CoreConcreteLibfunc::Gas(
+                GasConcreteLibfunc::WithdrawGas(_) | GasConcreteLibfunc::BuiltinWithdrawGas(_),
+            ) => self.range_check += 1,
+            CoreConcreteLibfunc::IntRange(IntRangeConcreteLibfunc::TryNew(_)) => {
+                self.range_check += 1
+            }
+            CoreConcreteLibfunc::Pedersen(PedersenConcreteLibfunc::PedersenHash(_)) => {
+                self.pedersen += 1
+            }
+            CoreConcreteLibfunc::Poseidon(PoseidonConcreteLibfunc::HadesPermutation(_)) => {
+                self.poseidon += 1
+            }
+            CoreConcreteLibfunc::QM31(QM31Concrete::Unpack(_)) => self.range_check += 5,
+            CoreConcreteLibfunc::Sint8(libfunc) => self.record_sint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Sint16(libfunc) => self.record_sint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Sint32(libfunc) => self.record_sint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Sint64(libfunc) => self.record_sint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Sint128(libfunc) => match libfunc {
+                Sint128Concrete::FromFelt252(_) => self.range_check += [1, 3][branch_idx],
+                Sint128Concrete::Operation(_) | Sint128Concrete::Diff(_) => self.range_check += 1,
+                _ => {}
+            },
+            CoreConcreteLibfunc::Starknet(
+                StarknetConcreteLibfunc::ClassHashTryFromFelt252(_)
+                | StarknetConcreteLibfunc::ContractAddressTryFromFelt252(_)
+                | StarknetConcreteLibfunc::StorageAddressTryFromFelt252(_)
+                | StarknetConcreteLibfunc::StorageBaseAddressFromFelt252(_),
+            ) => self.range_check += 3,
+            CoreConcreteLibfunc::Uint8(libfunc) => self.record_uint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Uint16(libfunc) => self.record_uint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Uint32(libfunc) => self.record_uint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Uint64(libfunc) => self.record_uint(libfunc, branch_idx),
+            CoreConcreteLibfunc::Uint128(libfunc) => match libfunc {
+                Uint128Concrete::Operation(_) => self.range_check += 1,
+                Uint128Concrete::Divmod(_) | Uint128Concrete::SquareRoot(_) => {
+                    self.range_check += 4
+                }
+                Uint128Concrete::MulGuaranteeVerify(_) => self.range_check += 9,
+                Uint128Concrete::FromFelt252(_) => self.range_check += [1, 3][branch_idx],
+                Uint128Concrete::Bitwise(_) => self.bitwise += 1,
+                Uint128Concrete::ByteReverse(_) => self.bitwise += 4,
+                _ => {}
+            },
+            CoreConcreteLibfunc::Uint256(libfunc) => match libfunc {
+                Uint256Concrete::IsZero(_) => {}
+                Uint256Concrete::Divmod(_) => self.range_check += 6,
+                Uint256Concrete::SquareRoot(_) => self.range_check += 7,
+                Uint256Concrete::InvModN(_) => self.range_check += [9, 7][branch_idx],
+            },
+            CoreConcreteLibfunc::Uint512(Uint512Concrete::DivModU256(_)) => self.range_check += 12,
+            _ => {}
+        }
+    }
+
+    fn record_uint<T: UintTraits + IntMulTraits + IsZeroTraits>(
+        &mut self,
+        libfunc: &UintConcrete<T>,
+        branch_idx: usize,
+    ) {
+        match libfunc {
+            UintConcrete::Operation(_) => self.range_check += 1,
+            UintConcrete::SquareRoot(_) => self.range_check += 4,
+            UintConcrete::FromFelt252(_) => self.range_check += [2, 3][branch_idx],
+            UintConcrete::Divmod(_) => self.range_check += 3,
+            UintConcrete::Bitwise(_) => self.bitwise += 1,
+            _ => {}
+        }
+    }
+
+    fn record_sint<T: SintTraits + IntMulTraits>(
+        &mut self,
+        libfunc: &SintConcrete<T>,
+        branch_idx: usize,
+    ) {
+        match libfunc {
+            SintConcrete::FromFelt252(_) => self.range_check += [2, 3][branch_idx],
+            SintConcrete::Operation(_) => self.range_check += [2, 1, 1][branch_idx],
+            SintConcrete::Diff(_) => self.range_check += 1,
+            _ => {}
+        }
+    }
+}
+
+fn circuit_info<'a>(
+    registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
+    ty: &ConcreteTypeId,
+) -> &'a CircuitInfo {
+    match registry.get_type(ty).unwrap() {
+        CoreTypeConcrete::Circuit(CircuitTypeConcrete::Circuit(info)) => &info.circuit_info,
+        _ => panic!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_test_vm, Value};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_builtin_usage() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type Pedersen = Pedersen;
+                    type u128 = u128;
+                    type felt252 = felt252;
+
+                    libfunc pedersen = pedersen;
+                    libfunc u128_overflowing_add = u128_overflowing_add;
+                    libfunc branch_align = branch_align;
+                    libfunc drop<u128> = drop<u128>;
+                    libfunc store_temp<RangeCheck> = store_temp<RangeCheck>;
+                    libfunc store_temp<Pedersen> = store_temp<Pedersen>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    pedersen([1], [4], [5]) -> ([1], [6]);
+                    u128_overflowing_add([0], [2], [3]) { fallthrough([0], [7]) 8([0], [7]) };
+                    branch_align() -> ();
+                    drop<u128>([7]) -> ();
+                    store_temp<RangeCheck>([0]) -> ([0]);
+                    store_temp<Pedersen>([1]) -> ([1]);
+                    store_temp<felt252>([6]) -> ([6]);
+                    return([0], [1], [6]);
+                    branch_align() -> ();
+                    drop<u128>([7]) -> ();
+                    store_temp<RangeCheck>([0]) -> ([0]);
+                    store_temp<Pedersen>([1]) -> ([1]);
+                    store_temp<felt252>([6]) -> ([6]);
+                    return([0], [1], [6]);
+
+                    main@0([0]: RangeCheck, [1]: Pedersen, [2]: u128, [3]: u128, [4]: felt252, [5]: felt252) -> (RangeCheck, Pedersen, felt252);
+                "#,
+            )
+            .unwrap();
+
+        let (vm, _) = run_test_vm(
+            program,
+            0,
+            vec![
+                Value::U128(u128::MAX),
+                Value::U128(1),
+                Value::Felt(1.into()),
+                Value::Felt(2.into()),
+            ],
+        );
+
+        let resources = vm.execution_resources();
+        assert_eq!(resources.n_steps, 8);
+        assert_eq!(
+            resources
+                .builtin_instance_counter()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            [("pedersen_builtin", 1), ("range_check_builtin", 1)]
+        );
+    }
+}
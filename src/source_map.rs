@@ -0,0 +1,161 @@
+use cairo_lang_sierra::{
+    debug_info::{Annotations, DebugInfo},
+    program::StatementIdx,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+/// Annotation namespace under which the compiler stores the statements' code locations.
+const CODE_LOCATIONS_NAMESPACE: &str = "github.com/software-mansion/cairo-coverage";
+
+/// Mapping from Sierra statements to the Cairo code they were generated from.
+///
+/// Built from the statement code locations that the compiler emits into the program's debug info
+/// when `add_statements_code_locations` is enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    locations: HashMap<StatementIdx, Vec<SourceLocation>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub start: SourcePosition,
+    /// End of the span, not included.
+    pub end: SourcePosition,
+    /// Whether the code comes from a macro expansion.
+    pub in_macro: bool,
+}
+
+/// A position in a Cairo source file. Both the line and the column are 0-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Extract the code locations from a program's debug info. Programs compiled without them
+    /// result in an empty map.
+    pub fn from_debug_info(debug_info: &DebugInfo) -> serde_json::Result<Self> {
+        Self::from_annotations(&debug_info.annotations)
+    }
+
+    pub fn from_annotations(annotations: &Annotations) -> serde_json::Result<Self> {
+        #[derive(Deserialize)]
+        struct RawAnnotation {
+            statements_code_locations: HashMap<usize, Vec<(String, RawSpan, bool)>>,
+        }
+
+        #[derive(Deserialize)]
+        struct RawSpan {
+            start: SourcePosition,
+            end: SourcePosition,
+        }
+
+        let Some(annotation) = annotations.get(CODE_LOCATIONS_NAMESPACE) else {
+            return Ok(Self::new());
+        };
+        let raw = RawAnnotation::deserialize(annotation)?;
+
+        Ok(Self {
+            locations: raw
+                .statements_code_locations
+                .into_iter()
+                .map(|(statement_idx, locations)| {
+                    let locations = locations
+                        .into_iter()
+                        .map(|(file, span, in_macro)| SourceLocation {
+                            file,
+                            start: span.start,
+                            end: span.end,
+                            in_macro,
+                        })
+                        .collect();
+                    (StatementIdx(statement_idx), locations)
+                })
+                .collect(),
+        })
+    }
+
+    pub fn insert(&mut self, statement_idx: StatementIdx, location: SourceLocation) {
+        self.locations
+            .entry(statement_idx)
+            .or_default()
+            .push(location);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// All the code locations that generated a statement. Statements from inlined functions have
+    /// more than one.
+    pub fn locations(&self, statement_idx: StatementIdx) -> &[SourceLocation] {
+        self.locations
+            .get(&statement_idx)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The main code location of a statement.
+    pub fn location(&self, statement_idx: StatementIdx) -> Option<&SourceLocation> {
+        self.locations(statement_idx).first()
+    }
+}
+
+impl Display for SourceLocation {
+    /// Renders the location as `file:line:col`, with 1-based lines and columns like compiler
+    /// diagnostics.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.file,
+            self.start.line + 1,
+            self.start.col + 1
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_annotations() {
+        let annotations: Annotations = OrderedHashMap::from([(
+            CODE_LOCATIONS_NAMESPACE.to_string(),
+            json!({
+                "statements_code_locations": {
+                    "3": [
+                        ["src/lib.cairo", {"start": {"line": 4, "col": 8}, "end": {"line": 4, "col": 13}}, false],
+                        ["src/main.cairo", {"start": {"line": 0, "col": 0}, "end": {"line": 0, "col": 5}}, true],
+                    ],
+                },
+            }),
+        )]);
+
+        let source_map = SourceMap::from_annotations(&annotations).unwrap();
+        assert_eq!(source_map.locations(StatementIdx(3)).len(), 2);
+        assert_eq!(source_map.locations(StatementIdx(4)), &[]);
+
+        let location = source_map.location(StatementIdx(3)).unwrap();
+        assert_eq!(location.end, SourcePosition { line: 4, col: 13 });
+        assert!(!location.in_macro);
+        assert_eq!(location.to_string(), "src/lib.cairo:5:9");
+
+        assert!(SourceMap::from_annotations(&Annotations::default())
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
+    any::Any,
     collections::{BTreeMap, VecDeque},
     iter::once,
+    sync::Arc,
 };
 
 pub use self::{
@@ -28,6 +30,9 @@ mod u256;
 
 pub type SyscallResult<T> = Result<T, Vec<Felt>>;
 
+/// Opaque state of a syscall handler, saved along with a VM snapshot.
+pub type SyscallHandlerSnapshot = Arc<dyn Any + Send + Sync>;
+
 pub trait StarknetSyscallHandler {
     fn get_block_hash(&mut self, block_number: u64, remaining_gas: &mut u64)
         -> SyscallResult<Felt>;
@@ -172,6 +177,15 @@ pub trait StarknetSyscallHandler {
     fn cheatcode(&mut self, _selector: Felt, _input: Vec<Felt>) -> Vec<Felt> {
         unimplemented!()
     }
+
+    /// Save the handler's state when the VM takes a snapshot or records the undo information of a
+    /// syscall. Handlers without state to rewind can keep the default.
+    fn snapshot(&self) -> Option<SyscallHandlerSnapshot> {
+        None
+    }
+
+    /// Restore a state returned by [`StarknetSyscallHandler::snapshot`].
+    fn restore(&mut self, _snapshot: &SyscallHandlerSnapshot) {}
 }
 
 /// A (somewhat) usable implementation of the starknet syscall handler trait.
@@ -236,6 +250,16 @@ impl Default for StubSyscallHandler {
 }
 
 impl StarknetSyscallHandler for StubSyscallHandler {
+    fn snapshot(&self) -> Option<SyscallHandlerSnapshot> {
+        Some(Arc::new(self.clone()))
+    }
+
+    fn restore(&mut self, snapshot: &SyscallHandlerSnapshot) {
+        if let Some(snapshot) = snapshot.downcast_ref::<Self>() {
+            self.clone_from(snapshot);
+        }
+    }
+
     fn get_block_hash(
         &mut self,
         block_number: u64,
@@ -0,0 +1,172 @@
+use cairo_lang_sierra::{
+    extensions::{
+        bounded_int::BoundedIntConcreteLibfunc,
+        core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
+        starknet::{secp256::Secp256ConcreteLibfunc, StarknetConcreteLibfunc},
+        ConcreteLibfunc,
+    },
+    program::Program,
+    program_registry::ProgramRegistry,
+};
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+/// The libfuncs and types declared by a program that the emulator can't execute.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct UnsupportedReport {
+    pub libfuncs: Vec<String>,
+    pub types: Vec<String>,
+}
+
+impl UnsupportedReport {
+    pub fn new(program: &Program, registry: &ProgramRegistry<CoreType, CoreLibfunc>) -> Self {
+        Self {
+            libfuncs: program
+                .libfunc_declarations
+                .iter()
+                .filter(|declaration| {
+                    !is_libfunc_supported(registry, registry.get_libfunc(&declaration.id).unwrap())
+                })
+                .map(|declaration| declaration.id.to_string())
+                .collect(),
+            types: program
+                .type_declarations
+                .iter()
+                .filter(|declaration| {
+                    !is_type_supported(registry.get_type(&declaration.id).unwrap())
+                })
+                .map(|declaration| declaration.id.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.libfuncs.is_empty() && self.types.is_empty()
+    }
+}
+
+impl Display for UnsupportedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for libfunc in &self.libfuncs {
+            writeln!(f, "libfunc {libfunc}")?;
+        }
+        for ty in &self.types {
+            writeln!(f, "type {ty}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the emulator can execute the libfunc.
+///
+/// Every variant is listed on purpose, so that libfuncs added by a new compiler version have to be
+/// classified here.
+pub fn is_libfunc_supported(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    libfunc: &CoreConcreteLibfunc,
+) -> bool {
+    match libfunc {
+        CoreConcreteLibfunc::ApTracking(_)
+        | CoreConcreteLibfunc::Array(_)
+        | CoreConcreteLibfunc::BranchAlign(_)
+        | CoreConcreteLibfunc::Blake(_)
+        | CoreConcreteLibfunc::Bool(_)
+        | CoreConcreteLibfunc::Box(_)
+        | CoreConcreteLibfunc::Cast(_)
+        | CoreConcreteLibfunc::Circuit(_)
+        | CoreConcreteLibfunc::Coupon(_)
+        | CoreConcreteLibfunc::CouponCall(_)
+        | CoreConcreteLibfunc::Drop(_)
+        | CoreConcreteLibfunc::Dup(_)
+        | CoreConcreteLibfunc::Ec(_)
+        | CoreConcreteLibfunc::Felt252(_)
+        | CoreConcreteLibfunc::Const(_)
+        | CoreConcreteLibfunc::FunctionCall(_)
+        | CoreConcreteLibfunc::Gas(_)
+        | CoreConcreteLibfunc::IntRange(_)
+        | CoreConcreteLibfunc::Uint8(_)
+        | CoreConcreteLibfunc::Uint16(_)
+        | CoreConcreteLibfunc::Uint32(_)
+        | CoreConcreteLibfunc::Uint64(_)
+        | CoreConcreteLibfunc::Uint128(_)
+        | CoreConcreteLibfunc::Uint256(_)
+        | CoreConcreteLibfunc::Uint512(_)
+        | CoreConcreteLibfunc::Sint128(_)
+        | CoreConcreteLibfunc::Mem(_)
+        | CoreConcreteLibfunc::UnwrapNonZero(_)
+        | CoreConcreteLibfunc::UnconditionalJump(_)
+        | CoreConcreteLibfunc::Enum(_)
+        | CoreConcreteLibfunc::Struct(_)
+        | CoreConcreteLibfunc::Felt252Dict(_)
+        | CoreConcreteLibfunc::Felt252DictEntry(_)
+        | CoreConcreteLibfunc::Felt252SquashedDict(_)
+        | CoreConcreteLibfunc::Pedersen(_)
+        | CoreConcreteLibfunc::Poseidon(_)
+        | CoreConcreteLibfunc::Debug(_)
+        | CoreConcreteLibfunc::SnapshotTake(_)
+        | CoreConcreteLibfunc::Bytes31(_)
+        | CoreConcreteLibfunc::Trace(_)
+        | CoreConcreteLibfunc::QM31(_) => true,
+        CoreConcreteLibfunc::Sint8(_)
+        | CoreConcreteLibfunc::Sint16(_)
+        | CoreConcreteLibfunc::Sint32(_)
+        | CoreConcreteLibfunc::Sint64(_)
+        | CoreConcreteLibfunc::Nullable(_) => false,
+        CoreConcreteLibfunc::BoundedInt(selector) => match selector {
+            BoundedIntConcreteLibfunc::Add(_)
+            | BoundedIntConcreteLibfunc::Sub(_)
+            | BoundedIntConcreteLibfunc::Mul(_)
+            | BoundedIntConcreteLibfunc::DivRem(_)
+            | BoundedIntConcreteLibfunc::WrapNonZero(_)
+            | BoundedIntConcreteLibfunc::TrimMin(_)
+            | BoundedIntConcreteLibfunc::TrimMax(_) => true,
+            // Only implemented for `i8` values.
+            BoundedIntConcreteLibfunc::Constrain(_) | BoundedIntConcreteLibfunc::IsZero(_) => {
+                let param = libfunc.param_signatures().last().unwrap();
+                matches!(
+                    registry.get_type(&param.ty).unwrap(),
+                    CoreTypeConcrete::Sint8(_)
+                )
+            }
+        },
+        CoreConcreteLibfunc::Starknet(selector) => match selector {
+            StarknetConcreteLibfunc::CallContract(_)
+            | StarknetConcreteLibfunc::ClassHashConst(_)
+            | StarknetConcreteLibfunc::ClassHashTryFromFelt252(_)
+            | StarknetConcreteLibfunc::ClassHashToFelt252(_)
+            | StarknetConcreteLibfunc::ContractAddressConst(_)
+            | StarknetConcreteLibfunc::ContractAddressTryFromFelt252(_)
+            | StarknetConcreteLibfunc::ContractAddressToFelt252(_)
+            | StarknetConcreteLibfunc::StorageRead(_)
+            | StarknetConcreteLibfunc::StorageWrite(_)
+            | StarknetConcreteLibfunc::StorageBaseAddressConst(_)
+            | StarknetConcreteLibfunc::StorageBaseAddressFromFelt252(_)
+            | StarknetConcreteLibfunc::StorageAddressFromBase(_)
+            | StarknetConcreteLibfunc::StorageAddressFromBaseAndOffset(_)
+            | StarknetConcreteLibfunc::StorageAddressToFelt252(_)
+            | StarknetConcreteLibfunc::StorageAddressTryFromFelt252(_)
+            | StarknetConcreteLibfunc::EmitEvent(_)
+            | StarknetConcreteLibfunc::GetBlockHash(_)
+            | StarknetConcreteLibfunc::GetExecutionInfo(_)
+            | StarknetConcreteLibfunc::GetExecutionInfoV2(_)
+            | StarknetConcreteLibfunc::Deploy(_)
+            | StarknetConcreteLibfunc::Keccak(_)
+            | StarknetConcreteLibfunc::Sha256ProcessBlock(_)
+            | StarknetConcreteLibfunc::Sha256StateHandleInit(_)
+            | StarknetConcreteLibfunc::Sha256StateHandleDigest(_)
+            | StarknetConcreteLibfunc::LibraryCall(_)
+            | StarknetConcreteLibfunc::ReplaceClass(_)
+            | StarknetConcreteLibfunc::SendMessageToL1(_)
+            | StarknetConcreteLibfunc::Secp256(Secp256ConcreteLibfunc::R1(_)) => true,
+            StarknetConcreteLibfunc::Testing(_)
+            | StarknetConcreteLibfunc::Secp256(Secp256ConcreteLibfunc::K1(_))
+            | StarknetConcreteLibfunc::GetClassHashAt(_)
+            | StarknetConcreteLibfunc::MetaTxV0(_) => false,
+        },
+    }
+}
+
+/// Whether the emulator can hold values of the type.
+pub fn is_type_supported(ty: &CoreTypeConcrete) -> bool {
+    !matches!(ty, CoreTypeConcrete::Span(_))
+}
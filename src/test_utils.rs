@@ -1,13 +1,22 @@
 #![cfg(test)]
 
-use std::{fs, path::Path, sync::Arc};
+use std::{fs, ops::Range, path::Path, sync::Arc};
 
 use cairo_lang_compiler::{
     compile_prepared_db, db::RootDatabase, diagnostics::DiagnosticsReporter,
     project::setup_project, CompilerConfig,
 };
 use cairo_lang_filesystem::db::init_dev_corelib;
-use cairo_lang_sierra::program::Program;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType},
+        ConcreteLibfunc,
+    },
+    ids::ConcreteLibfuncId,
+    program::Program,
+    program_registry::ProgramRegistry,
+    ProgramParser,
+};
 
 use crate::{starknet::StubSyscallHandler, Value, VirtualMachine};
 
@@ -52,6 +61,18 @@ pub(crate) fn load_cairo_from_str(cairo_str: &str) -> (String, Program) {
 }
 
 pub fn run_test_program(sierra_program: Program) -> Vec<Value> {
+    run_test_vm(sierra_program, 1000000, Vec::new()).1
+}
+
+/// Runs the `main` function of a program with the given gas and arguments, without the builtins,
+/// which are filled in by the VM. Returns the VM, to inspect what it recorded, and the values of
+/// the last state.
+pub fn run_test_vm(
+    sierra_program: Program,
+    initial_gas: u64,
+    args: Vec<Value>,
+) -> (VirtualMachine, Vec<Value>) {
+    let sierra_program = Arc::new(sierra_program);
     let function = sierra_program
         .funcs
         .iter()
@@ -63,22 +84,82 @@ pub fn run_test_program(sierra_program: Program) -> Vec<Value> {
         })
         .unwrap();
 
-    let mut vm = VirtualMachine::new(Arc::new(sierra_program.clone()));
-
-    let initial_gas = 1000000;
-
-    let args: &[Value] = &[];
-    vm.call_program(function, initial_gas, args.iter().cloned());
+    let mut vm = VirtualMachine::new(sierra_program.clone());
+    vm.call_program(function, initial_gas, args);
 
     let syscall_handler = &mut StubSyscallHandler::default();
     let trace = vm.run_with_trace(syscall_handler);
 
-    trace
+    let values = trace
         .states
         .last()
         .unwrap()
         .items
         .values()
         .cloned()
-        .collect()
+        .collect();
+    (vm, values)
+}
+
+/// Runs `libfunc` once on `args`, in a program generated from its signature. The `types` it uses
+/// must be declared by `declarations`. Builtins are filled in by the VM, so `args` only has the
+/// remaining parameters. Returns the index of the branch taken and its results.
+pub fn run_libfunc(declarations: &str, libfunc: &str, args: Vec<Value>) -> (usize, Vec<Value>) {
+    let declarations = format!(
+        "{declarations}\nlibfunc tested = {libfunc};\nlibfunc branch_align = branch_align;\n"
+    );
+    let program = ProgramParser::new().parse(&declarations).unwrap();
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program).unwrap();
+    let signature = registry
+        .get_libfunc(&ConcreteLibfuncId::from_string("tested"))
+        .unwrap();
+    let params = signature.param_signatures();
+    let branches = signature.branch_signatures();
+
+    // Every branch jumps to its own `branch_align` and `return`, so the statement the run stops
+    // at tells which one was taken.
+    let vars = |range: Range<usize>| {
+        range
+            .map(|id| format!("[{id}]"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let targets = branches
+        .iter()
+        .enumerate()
+        .map(|(index, branch)| {
+            let target = match index {
+                0 => "fallthrough".to_string(),
+                _ => (1 + 2 * index).to_string(),
+            };
+            let results = vars(params.len()..params.len() + branch.vars.len());
+            format!("{target}({results})")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let mut code = format!(
+        "{declarations}\ntested({}) {{ {targets} }};\n",
+        vars(0..params.len())
+    );
+    for _ in branches {
+        code.push_str("branch_align() -> ();\nreturn();\n");
+    }
+    let param_decls = params
+        .iter()
+        .enumerate()
+        .map(|(id, param)| format!("[{id}]: {}", param.ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+    code.push_str(&format!("\nmain@0({param_decls}) -> ();\n"));
+
+    let program = Arc::new(ProgramParser::new().parse(&code).unwrap());
+    let mut vm = VirtualMachine::new(program.clone());
+    vm.call_program(&program.funcs[0], 1000000, args);
+    let trace = vm.run_with_trace(&mut StubSyscallHandler::default());
+
+    let last = trace.states.last().unwrap();
+    (
+        (last.statement_idx.0 - 2) / 2,
+        last.items.values().cloned().collect(),
+    )
 }
@@ -5,17 +5,17 @@ use cairo_lang_sierra::{
         starknet::StarknetTypeConcrete,
         ConcreteType,
     },
-    ids::ConcreteTypeId,
+    ids::{ConcreteTypeId, FunctionId},
     program_registry::ProgramRegistry,
 };
 use num_bigint::{BigInt, BigUint};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use starknet_types_core::felt::Felt;
 use std::{collections::HashMap, fmt::Debug, ops::Range};
 
 use crate::{debug::type_to_name, gas::BuiltinCosts};
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     Array {
         ty: ConcreteTypeId,
@@ -28,6 +28,8 @@ pub enum Value {
     Circuit(Vec<BigUint>),
     CircuitModulus(BigUint),
     CircuitOutputs(Vec<BigUint>),
+    /// A prepaid call to a function.
+    Coupon(FunctionId),
     Enum {
         self_ty: ConcreteTypeId,
         index: usize,
@@ -37,15 +39,21 @@ pub enum Value {
     Bytes31(Felt),
     FeltDict {
         ty: ConcreteTypeId,
+        /// The current value of every accessed key.
         data: HashMap<Felt, Self>,
-        count: u64,
+        log: Vec<DictAccess>,
     },
     FeltDictEntry {
         ty: ConcreteTypeId,
         data: HashMap<Felt, Self>,
-        count: u64,
+        log: Vec<DictAccess>,
         key: Felt,
     },
+    /// A squashed dict, with a single access per key sorted by key.
+    FeltSquashedDict {
+        ty: ConcreteTypeId,
+        entries: Vec<DictAccess>,
+    },
     EcPoint {
         x: Felt,
         y: Felt,
@@ -58,9 +66,16 @@ pub enum Value {
     },
     I128(i128),
     I64(i64),
+    /// The integers in `[x, y)`, stored as values of the range's type.
+    IntRange {
+        x: Box<Self>,
+        y: Box<Self>,
+    },
     I32(i32),
     I16(i16),
     I8(i8),
+    /// An element of the degree-4 extension of the M31 field, as its four M31 coordinates.
+    QM31([u32; 4]),
     Struct(Vec<Self>),
     U256(u128, u128),
     U128(u128),
@@ -75,6 +90,14 @@ pub enum Value {
     Unit,
 }
 
+/// An access to a dict, which replaced the value of `key` from `prev_value` to `new_value`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DictAccess {
+    pub key: Felt,
+    pub prev_value: Value,
+    pub new_value: Value,
+}
+
 impl Value {
     pub fn default_for_type(
         registry: &ProgramRegistry<CoreType, CoreLibfunc>,
@@ -158,8 +181,9 @@ impl Value {
                 matches!(self, Self::Unit)
             }
 
-            // To do:
-            CoreTypeConcrete::Coupon(_) => todo!(),
+            CoreTypeConcrete::Coupon(info) => {
+                matches!(self, Self::Coupon(function_id) if *function_id == info.function_id)
+            }
             CoreTypeConcrete::Bitwise(_) => matches!(self, Self::Unit),
             CoreTypeConcrete::Box(info) => self.is(registry, &info.ty),
 
@@ -185,7 +209,8 @@ impl Value {
                     matches!(self, Self::Unit)
                 }
             },
-            CoreTypeConcrete::Const(_) => todo!(),
+            // Constants are only generic arguments, nothing is ever stored as one.
+            CoreTypeConcrete::Const(_) => false,
             CoreTypeConcrete::EcOp(_) => matches!(self, Self::Unit),
             CoreTypeConcrete::EcPoint(_) => matches!(self, Self::EcPoint { .. }),
             CoreTypeConcrete::EcState(_) => matches!(self, Self::EcState { .. }),
@@ -201,7 +226,7 @@ impl Value {
                 matches!(self, Self::FeltDictEntry { ty, .. } if *ty == info.ty)
             }
             CoreTypeConcrete::SquashedFelt252Dict(info) => {
-                matches!(self, Self::FeltDict { ty, .. } if *ty == info.ty)
+                matches!(self, Self::FeltSquashedDict { ty, .. } if *ty == info.ty)
             }
             CoreTypeConcrete::Pedersen(_) => matches!(self, Self::Unit),
             CoreTypeConcrete::Poseidon(_) => matches!(self, Self::Unit),
@@ -215,9 +240,15 @@ impl Value {
                 StarknetTypeConcrete::Secp256Point(_) => matches!(self, Self::Struct(_)),
                 StarknetTypeConcrete::Sha256StateHandle(_) => matches!(self, Self::Struct { .. }),
             },
-            CoreTypeConcrete::IntRange(_) => todo!(),
-            CoreTypeConcrete::Blake(_) => todo!(),
-            CoreTypeConcrete::QM31(_) => todo!(),
+            CoreTypeConcrete::IntRange(info) => {
+                matches!(self, Self::IntRange { x, y }
+                    if x.is(registry, &info.ty) && y.is(registry, &info.ty))
+            }
+            CoreTypeConcrete::Blake(_) => {
+                matches!(self, Self::Struct(words)
+                    if words.len() == 8 && words.iter().all(|word| matches!(word, Self::U32(_))))
+            }
+            CoreTypeConcrete::QM31(_) => matches!(self, Self::QM31(_)),
         };
 
         if !res {
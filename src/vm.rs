@@ -1,35 +1,52 @@
 use crate::{
     debug::libfunc_to_name,
-    gas::{BuiltinCosts, GasMetadata},
-    starknet::StarknetSyscallHandler,
-    ContractExecutionResult, ProgramTrace, StateDump, Value,
+    gas::{BuiltinCosts, GasMetadata, GasWallet},
+    observer::{self, InvocationContext, StatementContext, TracingObserver, VmObserver},
+    output::{OutputSink, PrintedOutput},
+    profiler::{GasProfiler, Profiler},
+    resources::ExecutionResources,
+    source_map::{SourceLocation, SourceMap},
+    starknet::{StarknetSyscallHandler, SyscallHandlerSnapshot},
+    support::UnsupportedReport,
+    watch::{WatchHit, Watchpoint},
+    CompactStep, CompactTrace, ContractExecutionResult, ProgramTrace, StateDump, Value,
 };
 use cairo_lang_sierra::{
     edit_state,
     extensions::{
         circuit::CircuitTypeConcrete,
         core::{CoreConcreteLibfunc, CoreLibfunc, CoreType, CoreTypeConcrete},
-        gas::CostTokenType,
+        ec::EcConcreteLibfunc,
+        gas::{CostTokenType, GasConcreteLibfunc},
         starknet::StarknetTypeConcrete,
         ConcreteLibfunc, ConcreteType,
     },
-    ids::{ConcreteLibfuncId, FunctionId, VarId},
+    ids::{ConcreteLibfuncId, ConcreteTypeId, FunctionId, VarId},
     program::{GenFunction, GenStatement, Invocation, Program, StatementIdx},
     program_registry::ProgramRegistry,
 };
 use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_sierra_type_size::{get_type_size_map, TypeSizeMap};
 use cairo_lang_starknet_classes::{
     casm_contract_class::ENTRY_POINT_COST, compiler_version::VersionId,
     contract_class::ContractEntryPoints,
 };
-use cairo_lang_utils::ordered_hash_map::OrderedHashMap;
+use cairo_lang_utils::{collection_arithmetics::sub_maps, ordered_hash_map::OrderedHashMap};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
 use smallvec::{smallvec, SmallVec};
 use starknet_types_core::felt::Felt;
-use std::{cmp::Ordering, fmt::Debug, sync::Arc};
-use tracing::{debug, trace};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::{Debug, Write},
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
 
 mod ap_tracking;
 mod array;
+mod blake;
 mod bool;
 mod bounded_int;
 mod r#box;
@@ -38,6 +55,8 @@ mod bytes31;
 mod cast;
 mod circuit;
 mod r#const;
+mod coupon;
+mod debug;
 mod drop;
 mod dup;
 mod ec;
@@ -45,16 +64,20 @@ mod r#enum;
 mod felt252;
 mod felt252_dict;
 mod felt252_dict_entry;
+mod felt252_squashed_dict;
 mod function_call;
 mod gas;
 mod int128;
+mod int_range;
 mod jump;
 mod mem;
 mod pedersen;
 mod poseidon;
+mod qm31;
 mod snapshot_take;
 mod starknet;
 mod r#struct;
+mod trace;
 mod uint128;
 mod uint16;
 mod uint252;
@@ -69,8 +92,29 @@ pub struct VirtualMachine {
     pub registry: Arc<ProgramRegistry<CoreType, CoreLibfunc>>,
     frames: Vec<SierraFrame>,
     pub gas: GasMetadata,
+    type_sizes: Arc<TypeSizeMap>,
+    /// Whether the program reads the gas wallet, which is only kept up to date in that case or
+    /// when profiling gas.
+    reads_gas_wallet: bool,
     entry_points: Option<ContractEntryPoints>,
     builtin_costs: BuiltinCosts,
+    profiler: Option<Profiler>,
+    gas_profiler: Option<GasProfiler>,
+    resources: ExecutionResources,
+    source_map: Option<Arc<SourceMap>>,
+    /// Undo information of every executed statement, when reverse stepping is enabled.
+    history: Option<Vec<UndoStep>>,
+    watchpoints: Vec<Watchpoint>,
+    /// The last watchpoint hit, until taken by the caller.
+    watch_hit: Option<WatchHit>,
+    observers: Vec<Arc<Mutex<dyn VmObserver + Send>>>,
+    /// Whether any observer needs the invocations.
+    observes_invocations: bool,
+    output_sink: OutputSink,
+    /// Text printed by the program, when captured.
+    output: Vec<PrintedOutput>,
+    /// Source of the random points picked by `ec_state_init`.
+    rng: ChaCha8Rng,
 }
 
 impl Debug for VirtualMachine {
@@ -87,11 +131,25 @@ impl VirtualMachine {
         let registry = ProgramRegistry::new(&program).unwrap();
         Self {
             gas: GasMetadata::new(&program, Some(MetadataComputationConfig::default())).unwrap(),
+            type_sizes: Arc::new(get_type_size_map(&program, &registry).unwrap_or_default()),
+            reads_gas_wallet: reads_gas_wallet(&program, &registry),
             program,
             registry: Arc::new(registry),
             frames: Vec::new(),
             entry_points: None,
             builtin_costs: Default::default(),
+            profiler: None,
+            gas_profiler: None,
+            resources: ExecutionResources::new(),
+            source_map: None,
+            history: None,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            observers: vec![Arc::new(Mutex::new(TracingObserver))],
+            observes_invocations: false,
+            output_sink: OutputSink::default(),
+            output: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(0),
         }
     }
 }
@@ -132,11 +190,25 @@ impl VirtualMachine {
                 }),
             )
             .unwrap(),
+            type_sizes: Arc::new(get_type_size_map(&program, &registry).unwrap_or_default()),
+            reads_gas_wallet: reads_gas_wallet(&program, &registry),
             program,
             registry: Arc::new(registry),
             frames: Vec::new(),
             entry_points: Some(entry_points.clone()),
             builtin_costs: Default::default(),
+            profiler: None,
+            gas_profiler: None,
+            resources: ExecutionResources::new(),
+            source_map: None,
+            history: None,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            observers: vec![Arc::new(Mutex::new(TracingObserver))],
+            observes_invocations: false,
+            output_sink: OutputSink::default(),
+            output: Vec::new(),
+            rng: ChaCha8Rng::seed_from_u64(0),
         }
     }
 
@@ -144,6 +216,287 @@ impl VirtualMachine {
         &self.registry
     }
 
+    /// The declared libfuncs and types the program can't be executed with.
+    pub fn unsupported(&self) -> UnsupportedReport {
+        UnsupportedReport::new(&self.program, &self.registry)
+    }
+
+    /// Start counting the executed steps. Any previous profile is discarded.
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new().with_source_map(self.source_map.clone()));
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    pub fn take_profiler(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    /// Start attributing the consumed gas. Any previous profile is discarded.
+    pub fn enable_gas_profiler(&mut self) {
+        self.gas_profiler = Some(GasProfiler::new().with_source_map(self.source_map.clone()));
+    }
+
+    pub fn gas_profiler(&self) -> Option<&GasProfiler> {
+        self.gas_profiler.as_ref()
+    }
+
+    pub fn take_gas_profiler(&mut self) -> Option<GasProfiler> {
+        self.gas_profiler.take()
+    }
+
+    /// Take a checkpoint of the execution state: the frames (including the gas counter, which
+    /// lives in them), the builtin costs, the execution resources, the random number generator,
+    /// the syscall handler's state and the amount of captured output.
+    ///
+    /// The profiler, the gas profiler and the observers (such as
+    /// [`Coverage`](crate::coverage::Coverage)) are not part of the snapshot: what they recorded
+    /// after it is kept by [`VirtualMachine::restore`].
+    pub fn snapshot(&self, syscall_handler: &impl StarknetSyscallHandler) -> VmSnapshot {
+        VmSnapshot {
+            frames: self.frames.clone(),
+            builtin_costs: self.builtin_costs,
+            resources: self.resources,
+            rng: self.rng.clone(),
+            syscall_handler: syscall_handler.snapshot(),
+            output_len: self.output.len(),
+        }
+    }
+
+    /// Go back to a checkpoint taken with [`VirtualMachine::snapshot`]. The recorded history, if
+    /// any, is discarded since it no longer matches the execution.
+    ///
+    /// The output captured since the checkpoint is dropped, but what was already written to
+    /// another [`OutputSink`] can't be taken back.
+    pub fn restore(
+        &mut self,
+        snapshot: &VmSnapshot,
+        syscall_handler: &mut impl StarknetSyscallHandler,
+    ) {
+        self.frames.clone_from(&snapshot.frames);
+        self.builtin_costs = snapshot.builtin_costs;
+        self.resources = snapshot.resources;
+        self.rng.clone_from(&snapshot.rng);
+        if let Some(state) = &snapshot.syscall_handler {
+            syscall_handler.restore(state);
+        }
+        self.output.truncate(snapshot.output_len);
+        self.watch_hit = None;
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    /// Record the information needed to undo every executed statement, enabling
+    /// [`VirtualMachine::step_back`]. Any previous history is discarded.
+    pub fn enable_history(&mut self) {
+        self.history = Some(Vec::new());
+    }
+
+    /// Number of statements that can be undone.
+    pub fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Undo the last executed statement and return its index, which is the next one to execute
+    /// again. Returns `None` when there is no history left.
+    ///
+    /// Like [`VirtualMachine::restore`], this doesn't rewind the profilers and the observers, so
+    /// the statement is counted again when it's re-executed.
+    pub fn step_back(
+        &mut self,
+        syscall_handler: &mut impl StarknetSyscallHandler,
+    ) -> Option<StatementIdx> {
+        let undo = self.history.as_mut()?.pop()?;
+
+        self.frames.truncate(undo.unchanged_frames);
+        self.frames.extend(undo.frames);
+        self.resources = undo.resources;
+        if let Some(rng) = undo.rng {
+            self.rng = rng;
+        }
+        if let Some(state) = &undo.syscall_handler {
+            syscall_handler.restore(state);
+        }
+        self.output.truncate(undo.output_len);
+        self.watch_hit = None;
+
+        self.frames.last().map(|frame| frame.pc)
+    }
+
+    /// Save the frames the next statement is going to modify.
+    fn undo_step(&self, syscall_handler: &impl StarknetSyscallHandler) -> Option<UndoStep> {
+        let frame = self.frames.last()?;
+        let (unchanged_frames, rng, syscall_handler) = match &self.program.statements[frame.pc.0] {
+            GenStatement::Invocation(invocation) => {
+                let libfunc = self.registry.get_libfunc(&invocation.libfunc_id).unwrap();
+                (
+                    self.frames.len() - 1,
+                    matches!(
+                        libfunc,
+                        CoreConcreteLibfunc::Ec(EcConcreteLibfunc::StateInit(_))
+                    )
+                    .then(|| self.rng.clone()),
+                    matches!(libfunc, CoreConcreteLibfunc::Starknet(_))
+                        .then(|| syscall_handler.snapshot())
+                        .flatten(),
+                )
+            }
+            // Returning also writes the results into the caller.
+            GenStatement::Return(_) => (self.frames.len().saturating_sub(2), None, None),
+        };
+
+        Some(UndoStep {
+            unchanged_frames,
+            frames: self.frames[unchanged_frames..].to_vec(),
+            resources: self.resources,
+            rng,
+            syscall_handler,
+            output_len: self.output.len(),
+        })
+    }
+
+    /// Register an observer to be notified of the execution. A [`TracingObserver`] is registered
+    /// by default.
+    pub fn add_observer(&mut self, observer: Arc<Mutex<dyn VmObserver + Send>>) {
+        self.observes_invocations |= observer.lock().unwrap().observes_invocations();
+        self.observers.push(observer);
+    }
+
+    /// Remove every observer, including the default [`TracingObserver`].
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+        self.observes_invocations = false;
+    }
+
+    /// Set where the text printed by the program goes. It's written to stdout by default.
+    pub fn set_output_sink(&mut self, output_sink: OutputSink) {
+        self.output_sink = output_sink;
+    }
+
+    /// Reseed the randomness used by `ec_state_init`. It's seeded with zero by default, so that
+    /// every run of a program produces the same trace.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    /// The text printed so far, when captured with [`OutputSink::Capture`].
+    pub fn output(&self) -> &[PrintedOutput] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<PrintedOutput> {
+        std::mem::take(&mut self.output)
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        self.watchpoints.push(watchpoint);
+        self.watchpoints.len() - 1
+    }
+
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        (index < self.watchpoints.len()).then(|| self.watchpoints.remove(index))
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// The watchpoint hit by the last executed statement, if any. It is cleared by the next step.
+    pub fn take_watch_hit(&mut self) -> Option<WatchHit> {
+        self.watch_hit.take()
+    }
+
+    /// Run until a watchpoint is hit. Returns `None` once the program has finished.
+    pub fn run_until_watch(
+        &mut self,
+        syscall_handler: &mut impl StarknetSyscallHandler,
+    ) -> Option<WatchHit> {
+        while self.step(syscall_handler).is_some() {
+            if let Some(hit) = self.take_watch_hit() {
+                return Some(hit);
+            }
+        }
+
+        None
+    }
+
+    /// Use the Cairo code locations of the statements in logs, errors, traces and profiles.
+    pub fn set_source_map(&mut self, source_map: SourceMap) {
+        let source_map = Arc::new(source_map);
+        if let Some(profiler) = &mut self.profiler {
+            profiler.source_map = Some(source_map.clone());
+        }
+        if let Some(gas_profiler) = &mut self.gas_profiler {
+            gas_profiler.source_map = Some(source_map.clone());
+        }
+        self.source_map = Some(source_map);
+    }
+
+    pub fn source_map(&self) -> Option<&SourceMap> {
+        self.source_map.as_deref()
+    }
+
+    pub(crate) fn frames(&self) -> &[SierraFrame] {
+        &self.frames
+    }
+
+    /// Read-only view of the call stack, outermost frame first.
+    pub fn call_stack(&self) -> Vec<CallFrame<'_>> {
+        self.frames
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| CallFrame {
+                function_id: &frame.function_id,
+                pc: frame.pc,
+                variables: &frame.state,
+                return_target: i
+                    .checked_sub(1)
+                    .map(|caller| self.return_target(&self.frames[caller])),
+            })
+            .collect()
+    }
+
+    /// Render the call stack, innermost frame first, using the Sierra debug names.
+    pub fn backtrace(&self) -> String {
+        self.frames
+            .iter()
+            .rev()
+            .enumerate()
+            .fold(String::new(), |mut output, (i, frame)| {
+                let statement = StatementDisplay(
+                    frame.pc,
+                    self.source_map
+                        .as_ref()
+                        .and_then(|map| map.location(frame.pc)),
+                );
+                writeln!(
+                    output,
+                    "#{i} {} at {statement}: {}",
+                    frame.function_id, self.program.statements[frame.pc.0]
+                )
+                .unwrap();
+                output
+            })
+    }
+
+    /// Statement a caller resumes at once the function it is calling returns.
+    fn return_target(&self, caller: &SierraFrame) -> StatementIdx {
+        match &self.program.statements[caller.pc.0] {
+            GenStatement::Invocation(Invocation { branches, .. }) => {
+                caller.pc.next(&branches[0].target)
+            }
+            GenStatement::Return(_) => unreachable!(),
+        }
+    }
+
+    /// Steps and builtins used by the statements executed so far.
+    pub fn execution_resources(&self) -> &ExecutionResources {
+        &self.resources
+    }
+
     /// Utility to call a contract.
     pub fn call_contract<I>(
         &mut self,
@@ -252,10 +605,7 @@ impl VirtualMachine {
                         | CoreTypeConcrete::Circuit(
                             CircuitTypeConcrete::AddMod(_) | CircuitTypeConcrete::MulMod(_),
                         ) => Value::Unit,
-                        CoreTypeConcrete::Starknet(inner) => match inner {
-                            StarknetTypeConcrete::System(_) => Value::Unit,
-                            _ => todo!(),
-                        },
+                        CoreTypeConcrete::Starknet(StarknetTypeConcrete::System(_)) => Value::Unit,
                         _ => iter.next().unwrap(),
                     }
                 })
@@ -273,18 +623,28 @@ impl VirtualMachine {
 
         let args = args.into_iter();
         assert_eq!(args.len(), function.params.len());
+        if let Some(profiler) = &mut self.profiler {
+            profiler.record_call(&function_id);
+        }
+        let state = function
+            .params
+            .iter()
+            .zip(args)
+            .map(|(param, value)| {
+                assert!(value.is(&self.registry, &param.ty));
+                (param.id.clone(), value)
+            })
+            .collect::<OrderedHashMap<_, _>>();
+        if !self.observers.is_empty() {
+            let args = state.values().cloned().collect::<Vec<_>>();
+            notify(&self.observers, |observer| {
+                observer.on_function_call(&function_id, &args)
+            });
+        }
         self.frames.push(SierraFrame {
-            _function_id: function_id,
-            state: function
-                .params
-                .iter()
-                .zip(args)
-                .map(|(param, value)| {
-                    assert!(value.is(&self.registry, &param.ty));
-                    (param.id.clone(), value)
-                })
-                .collect(),
-
+            gas_wallet: self.gas.initial_wallet(&function_id),
+            function_id,
+            state,
             pc: function.entry_point,
         })
     }
@@ -294,41 +654,124 @@ impl VirtualMachine {
         &mut self,
         syscall_handler: &mut impl StarknetSyscallHandler,
     ) -> Option<(StatementIdx, OrderedHashMap<VarId, Value>)> {
+        self.watch_hit = None;
+        if self.history.is_some() {
+            let undo = self.undo_step(syscall_handler);
+            if let (Some(history), Some(undo)) = (&mut self.history, undo) {
+                history.push(undo);
+            }
+        }
+
+        let call_stack = (self.profiler.is_some() || self.gas_profiler.is_some()).then(|| {
+            self.frames
+                .iter()
+                .map(|frame| frame.function_id.clone())
+                .collect::<Vec<_>>()
+        });
+        if let (Some(profiler), Some(call_stack)) = (&mut self.profiler, &call_stack) {
+            let frame = self.frames.last()?;
+            let libfunc = match &self.program.statements[frame.pc.0] {
+                GenStatement::Invocation(invocation) => Some(libfunc_to_name(
+                    self.registry.get_libfunc(&invocation.libfunc_id).unwrap(),
+                )),
+                GenStatement::Return(_) => None,
+            };
+            profiler.record_step(frame.pc, libfunc, call_stack);
+        }
+
         let frame = self.frames.last_mut()?;
+        self.resources.n_steps += 1;
 
         let pc_snapshot = frame.pc;
         let state_snapshot = frame.state.clone();
-
-        debug!(
-            "Evaluating statement {} ({})",
-            frame.pc.0, &self.program.statements[frame.pc.0],
+        let statement = StatementDisplay(
+            frame.pc,
+            self.source_map
+                .as_ref()
+                .and_then(|map| map.location(frame.pc)),
         );
-        trace!("values: \n{:#?}\n", state_snapshot);
+
+        notify(&self.observers, |observer| {
+            observer.before_statement(&StatementContext {
+                statement_idx: frame.pc,
+                statement: &self.program.statements[frame.pc.0],
+                function_id: &frame.function_id,
+                state: &state_snapshot,
+                libfunc: match &self.program.statements[frame.pc.0] {
+                    GenStatement::Invocation(invocation) => {
+                        Some(self.registry.get_libfunc(&invocation.libfunc_id).unwrap())
+                    }
+                    GenStatement::Return(_) => None,
+                },
+                location: statement.1,
+            })
+        });
         match &self.program.statements[frame.pc.0] {
             GenStatement::Invocation(invocation) => {
                 let libfunc = self.registry.get_libfunc(&invocation.libfunc_id).unwrap();
-                debug!(
-                    "Executing invocation of libfunc: {}",
-                    libfunc_to_name(libfunc)
-                );
                 let (state, values) =
                     edit_state::take_args(std::mem::take(&mut frame.state), invocation.args.iter())
                         .unwrap();
+                // Only keep the arguments, which may hold whole arrays and dicts, when they're
+                // used after the invocation consumes them.
+                let args = (self.observes_invocations || ExecutionResources::reads_args(libfunc))
+                    .then(|| values.clone())
+                    .unwrap_or_default();
+                let dict_keys_existed = self
+                    .watchpoints
+                    .iter()
+                    .map(|watchpoint| {
+                        watchpoint.dict_key().is_some_and(|key| {
+                            values.iter().any(|value| {
+                                matches!(value, Value::FeltDict { data, .. } if data.contains_key(key))
+                            })
+                        })
+                    })
+                    .collect::<SmallVec<[bool; 4]>>();
+                let gas_before = self.gas_profiler.is_some().then(|| {
+                    count_gas(
+                        &self.registry,
+                        libfunc.param_signatures().iter().map(|param| &param.ty),
+                        &values,
+                    )
+                });
+
+                let invocation_costs = if self.reads_gas_wallet || self.gas_profiler.is_some() {
+                    self.gas.get_invocation_costs(
+                        &self.registry,
+                        &self.type_sizes,
+                        frame.pc,
+                        libfunc,
+                    )
+                } else {
+                    Vec::new()
+                };
 
-                match eval(
+                let mut output = Vec::new();
+                let action = eval(
                     &self.registry,
                     &invocation.libfunc_id,
                     values,
                     syscall_handler,
                     &self.gas,
                     &frame.pc,
+                    &frame.gas_wallet,
                     self.builtin_costs,
-                ) {
+                    &mut self.rng,
+                    &mut output,
+                );
+                for text in output {
+                    if let Err(e) = self.output_sink.write(&mut self.output, frame.pc, text) {
+                        warn!("Failed to write the output of {statement}: {e}");
+                    }
+                }
+
+                match action {
                     EvalAction::NormalBranch(branch_idx, results) => {
                         assert_eq!(
                             results.len(),
                             invocation.branches[branch_idx].results.len(),
-                            "invocation of {invocation} returned the wrong number of values"
+                            "invocation of {invocation} at {statement} returned the wrong number of values"
                         );
 
                         assert!(
@@ -343,12 +786,91 @@ impl VirtualMachine {
                                         .vars
                                 )
                                 .all(|(value, ret)| value.is(&self.registry, &ret.ty)),
-                            "invocation of {} returned an invalid argument",
+                            "invocation of {} at {statement} returned an invalid argument",
                             libfunc_to_name(
                                 self.registry.get_libfunc(&invocation.libfunc_id).unwrap()
                             )
                         );
 
+                        if let (Some(gas_profiler), Some(gas_before), Some(call_stack)) =
+                            (&mut self.gas_profiler, gas_before, &call_stack)
+                        {
+                            let gas_after = count_gas(
+                                &self.registry,
+                                libfunc.branch_signatures()[branch_idx]
+                                    .vars
+                                    .iter()
+                                    .map(|var| &var.ty),
+                                &results,
+                            );
+                            // Gas moved between the counter and the wallet, like by
+                            // `withdraw_gas`, cancels out.
+                            let wallet_cost = invocation_costs
+                                .get(branch_idx)
+                                .map(|cost| self.builtin_costs.wallet_gas(cost))
+                                .unwrap_or_default();
+                            gas_profiler.record_gas(
+                                frame.pc,
+                                call_stack,
+                                gas_before - gas_after + wallet_cost,
+                            );
+                        }
+
+                        self.resources
+                            .record_libfunc(&self.registry, libfunc, branch_idx, &args);
+
+                        self.watch_hit = self
+                            .watchpoints
+                            .iter()
+                            .zip(dict_keys_existed)
+                            .enumerate()
+                            .find_map(|(index, (watchpoint, key_existed))| {
+                                let value = watchpoint.check(
+                                    frame.pc,
+                                    libfunc.branch_signatures()[branch_idx]
+                                        .vars
+                                        .iter()
+                                        .map(|var| &var.ty)
+                                        .zip(&results),
+                                    key_existed,
+                                )?;
+                                Some(WatchHit {
+                                    index,
+                                    statement_idx: frame.pc,
+                                    value: value.clone(),
+                                })
+                            });
+
+                        if self.observes_invocations {
+                            let invocation = InvocationContext {
+                                registry: &self.registry,
+                                statement_idx: frame.pc,
+                                libfunc,
+                                args: &args,
+                                branch_idx,
+                                results: &results,
+                            };
+                            let is_syscall = libfunc.param_signatures().iter().any(|param| {
+                                matches!(
+                                    self.registry.get_type(&param.ty).unwrap(),
+                                    CoreTypeConcrete::Starknet(StarknetTypeConcrete::System(_))
+                                )
+                            });
+                            notify(&self.observers, |observer| {
+                                observer.after_invocation(&invocation);
+                                if is_syscall {
+                                    observer.on_syscall(&invocation);
+                                }
+                            });
+                        }
+
+                        if let Some(cost) = invocation_costs.get(branch_idx) {
+                            frame.gas_wallet = sub_maps(
+                                std::mem::take(&mut frame.gas_wallet),
+                                cost.iter().map(|(token, count)| (*token, *count)),
+                            );
+                        }
+
                         frame.pc = frame.pc.next(&invocation.branches[branch_idx].target);
                         frame.state = edit_state::put_results(
                             state,
@@ -359,8 +881,33 @@ impl VirtualMachine {
                     EvalAction::FunctionCall(function_id, args) => {
                         let function = self.registry.get_function(&function_id).unwrap();
                         frame.state = state;
+                        if let Some(profiler) = &mut self.profiler {
+                            profiler.record_call(&function_id);
+                        }
+                        notify(&self.observers, |observer| {
+                            observer.on_function_call(&function_id, &args)
+                        });
+                        let callee_wallet = self.gas.initial_wallet(&function_id);
+                        if let Some(cost) = invocation_costs.first() {
+                            // The callee's own statements are charged from its wallet.
+                            if let (Some(gas_profiler), Some(call_stack)) =
+                                (&mut self.gas_profiler, &call_stack)
+                            {
+                                gas_profiler.record_gas(
+                                    frame.pc,
+                                    call_stack,
+                                    self.builtin_costs.wallet_gas(cost)
+                                        - self.builtin_costs.wallet_gas(&callee_wallet),
+                                );
+                            }
+                            frame.gas_wallet = sub_maps(
+                                std::mem::take(&mut frame.gas_wallet),
+                                cost.iter().map(|(token, count)| (*token, *count)),
+                            );
+                        }
                         self.frames.push(SierraFrame {
-                            _function_id: function_id,
+                            gas_wallet: callee_wallet,
+                            function_id,
                             state: function
                                 .params
                                 .iter()
@@ -375,10 +922,20 @@ impl VirtualMachine {
             }
             GenStatement::Return(ids) => {
                 let mut curr_frame = self.frames.pop().unwrap();
+                let (state, values) =
+                    edit_state::take_args(std::mem::take(&mut curr_frame.state), ids.iter())
+                        .unwrap();
+                notify(&self.observers, |observer| {
+                    for panic_data in values
+                        .iter()
+                        .filter_map(|value| observer::panic_data(&self.registry, value))
+                    {
+                        observer.on_panic(&curr_frame.function_id, &panic_data);
+                    }
+                    observer.on_return(&curr_frame.function_id, &values);
+                });
+
                 if let Some(prev_frame) = self.frames.last_mut() {
-                    let (state, values) =
-                        edit_state::take_args(std::mem::take(&mut curr_frame.state), ids.iter())
-                            .unwrap();
                     assert!(state.is_empty());
 
                     let target_branch = match &self.program.statements[prev_frame.pc.0] {
@@ -411,8 +968,39 @@ impl VirtualMachine {
         let mut trace = ProgramTrace::new();
 
         while let Some((statement_idx, state)) = self.step(syscall_handler) {
+            if let Some(location) = self
+                .source_map()
+                .and_then(|map| map.location(statement_idx))
+            {
+                trace.locations.insert(statement_idx, location.clone());
+            }
             trace.push(StateDump::new(statement_idx, state));
         }
+        trace.output = self.take_output();
+
+        trace
+    }
+
+    /// Run all the statement and return the delta-encoded trace.
+    pub fn run_with_compact_trace(
+        &mut self,
+        syscall_handler: &mut impl StarknetSyscallHandler,
+    ) -> CompactTrace {
+        let mut trace = CompactTrace::new();
+        let mut prev = BTreeMap::new();
+
+        while let Some((statement_idx, state)) = self.step(syscall_handler) {
+            if let Some(location) = self
+                .source_map()
+                .and_then(|map| map.location(statement_idx))
+            {
+                trace.locations.insert(statement_idx, location.clone());
+            }
+            let state = StateDump::new(statement_idx, state);
+            trace.push(CompactStep::diff(statement_idx, &prev, &state.items));
+            prev = state.items;
+        }
+        trace.output = self.take_output();
 
         trace
     }
@@ -432,12 +1020,107 @@ impl VirtualMachine {
     }
 }
 
+fn notify(
+    observers: &[Arc<Mutex<dyn VmObserver + Send>>],
+    mut callback: impl FnMut(&mut dyn VmObserver),
+) {
+    for observer in observers {
+        callback(&mut *observer.lock().unwrap());
+    }
+}
+
+fn reads_gas_wallet(program: &Program, registry: &ProgramRegistry<CoreType, CoreLibfunc>) -> bool {
+    program.libfunc_declarations.iter().any(|declaration| {
+        matches!(
+            registry.get_libfunc(&declaration.id).unwrap(),
+            CoreConcreteLibfunc::Gas(GasConcreteLibfunc::GetUnspentGas(_))
+        )
+    })
+}
+
+/// Sum the values of the gas counters among `values`.
+fn count_gas<'a>(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    types: impl Iterator<Item = &'a ConcreteTypeId>,
+    values: &[Value],
+) -> i128 {
+    types
+        .zip(values)
+        .filter_map(
+            |(ty, value)| match (registry.get_type(ty).unwrap(), value) {
+                (CoreTypeConcrete::GasBuiltin(_), Value::U64(gas)) => Some(*gas as i128),
+                _ => None,
+            },
+        )
+        .sum()
+}
+
+/// A statement index, along with its Cairo code location when known.
+struct StatementDisplay<'a>(StatementIdx, Option<&'a SourceLocation>);
+
+impl std::fmt::Display for StatementDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "statement {}", self.0 .0)?;
+        if let Some(location) = self.1 {
+            write!(f, " ({location})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checkpoint of a [`VirtualMachine`], taken with [`VirtualMachine::snapshot`].
 #[derive(Clone, Debug)]
-struct SierraFrame {
-    _function_id: FunctionId,
+pub struct VmSnapshot {
+    frames: Vec<SierraFrame>,
+    builtin_costs: BuiltinCosts,
+    resources: ExecutionResources,
+    rng: ChaCha8Rng,
+    syscall_handler: Option<SyscallHandlerSnapshot>,
+    output_len: usize,
+}
 
-    state: OrderedHashMap<VarId, Value>,
-    pc: StatementIdx,
+/// What a single statement changed, to be able to undo it.
+#[derive(Clone, Debug)]
+struct UndoStep {
+    /// Number of frames, from the outermost one, that the statement left untouched.
+    unchanged_frames: usize,
+    /// The frames after those, as they were before the statement.
+    frames: Vec<SierraFrame>,
+    resources: ExecutionResources,
+    /// State of the random number generator before an `ec_state_init`.
+    rng: Option<ChaCha8Rng>,
+    /// State of the syscall handler before a syscall.
+    syscall_handler: Option<SyscallHandlerSnapshot>,
+    /// Amount of captured output before the statement.
+    output_len: usize,
+}
+
+/// A frame of the call stack, as exposed by [`VirtualMachine::call_stack`].
+#[derive(Clone, Copy, Debug)]
+pub struct CallFrame<'a> {
+    pub function_id: &'a FunctionId,
+    /// The statement that will be executed next.
+    pub pc: StatementIdx,
+    /// The variables alive at `pc`.
+    pub variables: &'a OrderedHashMap<VarId, Value>,
+    /// Where the caller resumes once this frame returns. `None` for the entry point.
+    pub return_target: Option<StatementIdx>,
+}
+
+impl CallFrame<'_> {
+    pub fn debug_name(&self) -> Option<&str> {
+        self.function_id.debug_name.as_deref()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SierraFrame {
+    pub(crate) function_id: FunctionId,
+    /// The gas pre-charged for this call and not yet consumed.
+    pub(crate) gas_wallet: GasWallet,
+
+    pub(crate) state: OrderedHashMap<VarId, Value>,
+    pub(crate) pc: StatementIdx,
 }
 
 enum EvalAction {
@@ -445,6 +1128,7 @@ enum EvalAction {
     FunctionCall(FunctionId, SmallVec<[Value; 2]>),
 }
 
+#[allow(clippy::too_many_arguments)]
 fn eval<'a>(
     registry: &'a ProgramRegistry<CoreType, CoreLibfunc>,
     id: &'a ConcreteLibfuncId,
@@ -452,7 +1136,10 @@ fn eval<'a>(
     syscall_handler: &mut impl StarknetSyscallHandler,
     gas: &GasMetadata,
     statement_idx: &StatementIdx,
+    gas_wallet: &GasWallet,
     builtin_costs: BuiltinCosts,
+    rng: &mut ChaCha8Rng,
+    output: &mut Vec<String>,
 ) -> EvalAction {
     match registry.get_libfunc(id).unwrap() {
         CoreConcreteLibfunc::ApTracking(selector) => {
@@ -469,12 +1156,14 @@ fn eval<'a>(
         CoreConcreteLibfunc::Cast(selector) => self::cast::eval(registry, selector, args),
         CoreConcreteLibfunc::Circuit(selector) => self::circuit::eval(registry, selector, args),
         CoreConcreteLibfunc::Const(selector) => self::r#const::eval(registry, selector, args),
-        CoreConcreteLibfunc::Coupon(_) => todo!(),
-        CoreConcreteLibfunc::CouponCall(_) => todo!(),
-        CoreConcreteLibfunc::Debug(_) => todo!(),
+        CoreConcreteLibfunc::Coupon(selector) => self::coupon::eval(registry, selector, args),
+        CoreConcreteLibfunc::CouponCall(info) => {
+            self::function_call::eval_coupon_call(registry, info, args)
+        }
+        CoreConcreteLibfunc::Debug(selector) => self::debug::eval(registry, selector, args, output),
         CoreConcreteLibfunc::Drop(info) => self::drop::eval(registry, info, args),
         CoreConcreteLibfunc::Dup(info) => self::dup::eval(registry, info, args),
-        CoreConcreteLibfunc::Ec(selector) => self::ec::eval(registry, selector, args),
+        CoreConcreteLibfunc::Ec(selector) => self::ec::eval(registry, selector, args, rng),
         CoreConcreteLibfunc::Enum(selector) => self::r#enum::eval(registry, selector, args),
         CoreConcreteLibfunc::Felt252(selector) => self::felt252::eval(registry, selector, args),
         CoreConcreteLibfunc::Felt252Dict(selector) => {
@@ -488,8 +1177,9 @@ fn eval<'a>(
             registry,
             selector,
             args,
-            &gas,
+            gas,
             *statement_idx,
+            gas_wallet,
             builtin_costs,
         ),
         CoreConcreteLibfunc::Mem(selector) => self::mem::eval(registry, selector, args),
@@ -519,10 +1209,207 @@ fn eval<'a>(
 
             EvalAction::NormalBranch(0, smallvec![value])
         }
-        CoreConcreteLibfunc::IntRange(_) => todo!(),
-        CoreConcreteLibfunc::Blake(_) => todo!(),
-        CoreConcreteLibfunc::QM31(_) => todo!(),
-        CoreConcreteLibfunc::Felt252SquashedDict(_) => todo!(),
-        CoreConcreteLibfunc::Trace(_) => todo!(),
+        CoreConcreteLibfunc::IntRange(selector) => self::int_range::eval(registry, selector, args),
+        CoreConcreteLibfunc::Blake(selector) => self::blake::eval(registry, selector, args),
+        CoreConcreteLibfunc::QM31(selector) => self::qm31::eval(registry, selector, args),
+        CoreConcreteLibfunc::Felt252SquashedDict(selector) => {
+            self::felt252_squashed_dict::eval(registry, selector, args)
+        }
+        CoreConcreteLibfunc::Trace(info) => self::trace::eval(registry, info, args, output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{starknet::StubSyscallHandler, watch::WatchCondition};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_call_stack() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@double> = function_call<user@double>;
+
+                    function_call<user@double>([0]) -> ([1]);
+                    store_temp<felt252>([1]) -> ([1]);
+                    return([1]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (felt252);
+                    double@3([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+        vm.step(&mut StubSyscallHandler::default());
+
+        let call_stack = vm.call_stack();
+        assert_eq!(call_stack.len(), 2);
+        assert_eq!(call_stack[0].debug_name(), Some("main"));
+        assert_eq!(call_stack[0].return_target, None);
+        assert_eq!(call_stack[1].debug_name(), Some("double"));
+        assert_eq!(call_stack[1].pc, StatementIdx(3));
+        assert_eq!(call_stack[1].return_target, Some(StatementIdx(1)));
+        assert_eq!(
+            call_stack[1].variables.iter().collect::<Vec<_>>(),
+            [(&VarId::new(0), &Value::Felt(21.into()))]
+        );
+
+        assert_eq!(
+            vm.backtrace(),
+            "#0 double at statement 3: dup<felt252>([0]) -> ([0], [1])\n\
+             #1 main at statement 0: function_call<user@double>([0]) -> ([1])\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_step_back() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc function_call<user@double> = function_call<user@double>;
+
+                    function_call<user@double>([0]) -> ([1]);
+                    store_temp<felt252>([1]) -> ([1]);
+                    return([1]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (felt252);
+                    double@3([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.enable_history();
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+
+        let mut states = Vec::new();
+        while !vm.frames.is_empty() {
+            states.push((vm.frames.clone(), vm.execution_resources().n_steps));
+            vm.step(syscall_handler);
+        }
+        assert_eq!(vm.history_len(), 7);
+
+        let snapshot = vm.snapshot(syscall_handler);
+        while let Some((frames, n_steps)) = states.pop() {
+            let pc = vm.step_back(syscall_handler).unwrap();
+            assert_eq!(pc, frames.last().unwrap().pc);
+            assert_eq!(format!("{:?}", vm.frames), format!("{frames:?}"));
+            assert_eq!(vm.execution_resources().n_steps, n_steps);
+        }
+        assert_eq!(vm.step_back(syscall_handler), None);
+
+        vm.restore(&snapshot, syscall_handler);
+        assert!(vm.frames.is_empty());
+        assert_eq!(vm.execution_resources().n_steps, 7);
+        assert_eq!(vm.history_len(), 0);
+    }
+
+    #[test]
+    fn test_step_back_output() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc felt252_const<104> = felt252_const<104>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc print = print;
+
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<104>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    print([0]) -> ();
+                    return();
+
+                    main@0() -> ();
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.set_output_sink(OutputSink::Capture);
+        vm.enable_history();
+        vm.call_program(&program.funcs[0], 0, []);
+
+        let snapshot = vm.snapshot(syscall_handler);
+        while vm.step(syscall_handler).is_some() {}
+        assert_eq!(vm.output().len(), 1);
+
+        vm.step_back(syscall_handler);
+        assert_eq!(vm.output().len(), 1);
+        vm.step_back(syscall_handler);
+        assert!(vm.output().is_empty());
+
+        while vm.step(syscall_handler).is_some() {}
+        assert_eq!(vm.output().len(), 1);
+        vm.restore(&snapshot, syscall_handler);
+        assert!(vm.output().is_empty());
+    }
+
+    #[test]
+    fn test_rewind_clears_watch_hit() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_const<104> = felt252_const<104>;
+                    libfunc drop<felt252> = drop<felt252>;
+
+                    felt252_const<104>() -> ([0]);
+                    drop<felt252>([0]) -> ();
+                    return();
+
+                    main@0() -> ();
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.enable_history();
+        vm.add_watchpoint(Watchpoint::new(WatchCondition::Value(Value::Felt(
+            104.into(),
+        ))));
+        vm.call_program(&program.funcs[0], 0, []);
+        let snapshot = vm.snapshot(syscall_handler);
+
+        vm.step(syscall_handler);
+        vm.step_back(syscall_handler);
+        assert_eq!(vm.take_watch_hit(), None);
+
+        vm.step(syscall_handler);
+        vm.restore(&snapshot, syscall_handler);
+        assert_eq!(vm.take_watch_hit(), None);
     }
 }
@@ -2,7 +2,7 @@ use super::EvalAction;
 use crate::{find_real_type, Value};
 use cairo_lang_sierra::{
     extensions::{
-        array::ArrayConcreteLibfunc,
+        array::{ArrayConcreteLibfunc, ConcreteMultiPopLibfunc},
         core::{CoreLibfunc, CoreType, CoreTypeConcrete},
         lib_func::{SignatureAndTypeConcreteLibfunc, SignatureOnlyConcreteLibfunc},
         ConcreteLibfunc,
@@ -19,10 +19,10 @@ pub fn eval(
     match selector {
         ArrayConcreteLibfunc::New(info) => eval_new(registry, info, args),
         ArrayConcreteLibfunc::SpanFromTuple(info) => eval_span_from_tuple(registry, info, args),
-        ArrayConcreteLibfunc::TupleFromSpan(_) => todo!(),
+        ArrayConcreteLibfunc::TupleFromSpan(info) => eval_tuple_from_span(registry, info, args),
         ArrayConcreteLibfunc::Append(info) => eval_append(registry, info, args),
         ArrayConcreteLibfunc::PopFront(info) => eval_pop_front(registry, info, args),
-        ArrayConcreteLibfunc::PopFrontConsume(_) => todo!(),
+        ArrayConcreteLibfunc::PopFrontConsume(info) => eval_pop_front_consume(registry, info, args),
         ArrayConcreteLibfunc::Get(info) => eval_get(registry, info, args),
         ArrayConcreteLibfunc::Slice(info) => eval_slice(registry, info, args),
         ArrayConcreteLibfunc::Len(info) => eval_len(registry, info, args),
@@ -33,7 +33,9 @@ pub fn eval(
         ArrayConcreteLibfunc::SnapshotMultiPopFront(info) => {
             eval_snapshot_multi_pop_front(registry, info, args)
         }
-        ArrayConcreteLibfunc::SnapshotMultiPopBack(_) => todo!(),
+        ArrayConcreteLibfunc::SnapshotMultiPopBack(info) => {
+            eval_snapshot_multi_pop_back(registry, info, args)
+        }
     }
 }
 
@@ -61,6 +63,29 @@ fn eval_span_from_tuple(
     EvalAction::NormalBranch(0, smallvec![value])
 }
 
+fn eval_tuple_from_span(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndTypeConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::Array { data, .. }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    let ty = &info.branch_signatures()[0].vars[0].ty;
+    let ty = find_real_type(registry, ty);
+
+    let CoreTypeConcrete::Struct(info) = registry.get_type(&ty).unwrap() else {
+        panic!()
+    };
+
+    if data.len() == info.members.len() {
+        EvalAction::NormalBranch(0, smallvec![Value::Struct(data)])
+    } else {
+        EvalAction::NormalBranch(1, smallvec![])
+    }
+}
+
 pub fn eval_new(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     info: &SignatureOnlyConcreteLibfunc,
@@ -175,6 +200,24 @@ pub fn eval_pop_front(
     }
 }
 
+pub fn eval_pop_front_consume(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureAndTypeConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::Array { mut data, ty }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    if !data.is_empty() {
+        let new_data = data.split_off(1);
+        let value = data[0].clone();
+        EvalAction::NormalBranch(0, smallvec![Value::Array { data: new_data, ty }, value])
+    } else {
+        EvalAction::NormalBranch(1, smallvec![])
+    }
+}
+
 pub fn eval_snapshot_pop_front(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     info: &SignatureAndTypeConcreteLibfunc,
@@ -215,7 +258,7 @@ pub fn eval_snapshot_pop_back(
 
 fn eval_snapshot_multi_pop_front(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
-    info: &cairo_lang_sierra::extensions::array::ConcreteMultiPopLibfunc,
+    info: &ConcreteMultiPopLibfunc,
     args: Vec<Value>,
 ) -> EvalAction {
     let [rangecheck, Value::Array { mut data, ty }]: [Value; 2] = args.try_into().unwrap() else {
@@ -238,3 +281,113 @@ fn eval_snapshot_multi_pop_front(
         EvalAction::NormalBranch(1, smallvec![rangecheck, Value::Array { data, ty }])
     }
 }
+
+fn eval_snapshot_multi_pop_back(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &ConcreteMultiPopLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [rangecheck, Value::Array { mut data, ty }]: [Value; 2] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    let CoreTypeConcrete::Struct(popped_cty) = registry.get_type(&info.popped_ty).unwrap() else {
+        panic!()
+    };
+
+    if data.len() >= popped_cty.members.len() {
+        let popped_data = data.split_off(data.len() - popped_cty.members.len());
+        let value = Value::Struct(popped_data);
+        assert!(value.is(registry, &info.popped_ty));
+        EvalAction::NormalBranch(0, smallvec![rangecheck, Value::Array { data, ty }, value])
+    } else {
+        EvalAction::NormalBranch(1, smallvec![rangecheck, Value::Array { data, ty }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_libfunc, Value};
+    use cairo_lang_sierra::ids::ConcreteTypeId;
+
+    const TYPES: &str = r#"
+        type RangeCheck = RangeCheck;
+        type felt252 = felt252;
+        type Array<felt252> = Array<felt252>;
+        type Snapshot<Array<felt252>> = Snapshot<Array<felt252>>;
+        type Box<felt252> = Box<felt252>;
+        type Pair = Struct<ut@Tuple, felt252, felt252>;
+        type Box<Pair> = Box<Pair>;
+    "#;
+
+    fn felts(values: &[u64]) -> Vec<Value> {
+        values
+            .iter()
+            .map(|value| Value::Felt((*value).into()))
+            .collect()
+    }
+
+    fn array(values: &[u64]) -> Value {
+        Value::Array {
+            ty: ConcreteTypeId::from_string("felt252"),
+            data: felts(values),
+        }
+    }
+
+    #[test]
+    fn test_tuple_from_span() {
+        let tuple_from_span =
+            |values: &[u64]| run_libfunc(TYPES, "tuple_from_span<Pair>", vec![array(values)]);
+
+        assert_eq!(
+            tuple_from_span(&[1, 2]),
+            (0, vec![Value::Struct(felts(&[1, 2]))])
+        );
+        assert_eq!(tuple_from_span(&[1, 2, 3]), (1, vec![]));
+        assert_eq!(tuple_from_span(&[]), (1, vec![]));
+    }
+
+    #[test]
+    fn test_array_pop_front_consume() {
+        let pop_front_consume = |values: &[u64]| {
+            run_libfunc(
+                TYPES,
+                "array_pop_front_consume<felt252>",
+                vec![array(values)],
+            )
+        };
+
+        assert_eq!(
+            pop_front_consume(&[1, 2, 3]),
+            (0, vec![array(&[2, 3]), Value::Felt(1.into())])
+        );
+        assert_eq!(pop_front_consume(&[]), (1, vec![]));
+    }
+
+    #[test]
+    fn test_array_snapshot_multi_pop_back() {
+        let multi_pop_back = |values: &[u64]| {
+            run_libfunc(
+                TYPES,
+                "array_snapshot_multi_pop_back<Pair>",
+                vec![array(values)],
+            )
+        };
+
+        assert_eq!(
+            multi_pop_back(&[1, 2, 3, 4]),
+            (
+                0,
+                vec![Value::Unit, array(&[1, 2]), Value::Struct(felts(&[3, 4]))]
+            )
+        );
+        assert_eq!(
+            multi_pop_back(&[1, 2]),
+            (
+                0,
+                vec![Value::Unit, array(&[]), Value::Struct(felts(&[1, 2]))]
+            )
+        );
+        assert_eq!(multi_pop_back(&[1]), (1, vec![Value::Unit, array(&[1])]));
+    }
+}
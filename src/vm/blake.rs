@@ -0,0 +1,232 @@
+use super::EvalAction;
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        blake::BlakeConcreteLibfunc,
+        core::{CoreLibfunc, CoreType},
+        lib_func::SignatureOnlyConcreteLibfunc,
+    },
+    program_registry::ProgramRegistry,
+};
+use smallvec::smallvec;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &BlakeConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    match selector {
+        BlakeConcreteLibfunc::Blake2sCompress(info) => eval_compress(registry, info, args, false),
+        BlakeConcreteLibfunc::Blake2sFinalize(info) => eval_compress(registry, info, args, true),
+    }
+}
+
+/// Compress a 16-word block into the state. `byte_count` is the number of bytes hashed so far,
+/// including this block.
+pub fn eval_compress(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+    finalize: bool,
+) -> EvalAction {
+    let [Value::Struct(state), Value::U32(byte_count), Value::Struct(message)]: [Value; 3] =
+        args.try_into().unwrap()
+    else {
+        panic!()
+    };
+
+    let state: [u32; 8] = state
+        .into_iter()
+        .map(|v| {
+            let Value::U32(v) = v else { panic!() };
+            v
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+    let message: [u32; 16] = message
+        .into_iter()
+        .map(|v| {
+            let Value::U32(v) = v else { panic!() };
+            v
+        })
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+
+    let state = blake2s_compress(state, &message, byte_count, finalize);
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![Value::Struct(state.into_iter().map(Value::U32).collect())],
+    )
+}
+
+/// The Blake2s compression function, for messages shorter than 4GiB.
+fn blake2s_compress(h: [u32; 8], message: &[u32; 16], byte_count: u32, finalize: bool) -> [u32; 8] {
+    let mut v = [0; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&IV);
+    v[12] ^= byte_count;
+    if finalize {
+        v[14] = !v[14];
+    }
+
+    for sigma in SIGMA {
+        for (i, [a, b, c, d]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (x, y) = (message[sigma[2 * i]], message[sigma[2 * i + 1]]);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+            v[d] = (v[d] ^ v[a]).rotate_right(16);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(12);
+            v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+            v[d] = (v[d] ^ v[a]).rotate_right(8);
+            v[c] = v[c].wrapping_add(v[d]);
+            v[b] = (v[b] ^ v[c]).rotate_right(7);
+        }
+    }
+
+    std::array::from_fn(|i| h[i] ^ v[i] ^ v[i + 8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::run_libfunc;
+
+    const TYPES: &str = r#"
+        type u32 = u32;
+        type U32x8 = Struct<ut@Tuple, u32, u32, u32, u32, u32, u32, u32, u32>;
+        type U32x16 = Struct<ut@Tuple, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32>;
+        type Box<U32x8> = Box<U32x8>;
+        type Box<U32x16> = Box<U32x16>;
+    "#;
+
+    /// From RFC 7693, appendix B.
+    const ABC_DIGEST: [u8; 32] = [
+        0x50, 0x8C, 0x5E, 0x8C, 0x32, 0x7C, 0x14, 0xE2, 0xE1, 0xA7, 0x2B, 0xA3, 0x4E, 0xEB, 0x45,
+        0x2F, 0x37, 0x45, 0x8B, 0x20, 0x9E, 0xD6, 0x3A, 0x29, 0x4D, 0x99, 0x9B, 0x4C, 0x86, 0x67,
+        0x59, 0x82,
+    ];
+
+    /// From `testvectors/blake2-kat.json` in the BLAKE2 reference implementation
+    /// (https://github.com/BLAKE2/BLAKE2): the unkeyed hash of the 100 bytes `00 01 .. 63`.
+    const TWO_BLOCKS_DIGEST: [u8; 32] = [
+        0x81, 0xDC, 0xC3, 0xA5, 0x05, 0xEA, 0xCE, 0x3F, 0x87, 0x9D, 0x8F, 0x70, 0x27, 0x76, 0x77,
+        0x0F, 0x9D, 0xF5, 0x0E, 0x52, 0x1D, 0x14, 0x28, 0xA8, 0x5D, 0xAF, 0x04, 0xF9, 0xAD, 0x21,
+        0x50, 0xE0,
+    ];
+
+    /// A full block compressed before the final one.
+    fn two_blocks() -> Vec<u8> {
+        (0..100).collect()
+    }
+
+    /// Hash a message with Blake2s-256, without a key.
+    fn blake2s(data: &[u8]) -> [u8; 32] {
+        let mut state = IV;
+        state[0] ^= 0x01010020;
+
+        let blocks = data.chunks(64).collect::<Vec<_>>();
+        for (i, block) in blocks.iter().enumerate() {
+            let mut bytes = [0; 64];
+            bytes[..block.len()].copy_from_slice(block);
+            let message = std::array::from_fn(|j| {
+                u32::from_le_bytes(bytes[4 * j..4 * j + 4].try_into().unwrap())
+            });
+
+            let is_last = i == blocks.len() - 1;
+            let byte_count = (64 * i + block.len()) as u32;
+            state = blake2s_compress(state, &message, byte_count, is_last);
+        }
+
+        let mut digest = [0; 32];
+        for (chunk, word) in digest.chunks_mut(4).zip(state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    #[test]
+    fn test_blake2s_compress() {
+        assert_eq!(blake2s(b"abc"), ABC_DIGEST);
+        assert_eq!(blake2s(&two_blocks()), TWO_BLOCKS_DIGEST);
+    }
+
+    /// Hash a message of up to two blocks with Blake2s-256, by running the libfuncs in the VM.
+    fn blake2s_in_vm(data: &[u8]) -> [u8; 32] {
+        let words =
+            |values: &[u32]| Value::Struct(values.iter().copied().map(Value::U32).collect());
+
+        let mut state = IV;
+        state[0] ^= 0x01010020;
+        let mut state = words(&state);
+
+        let blocks = data.chunks(64).collect::<Vec<_>>();
+        for (i, block) in blocks.iter().enumerate() {
+            let mut bytes = [0; 64];
+            bytes[..block.len()].copy_from_slice(block);
+            let message = (0..16)
+                .map(|j| u32::from_le_bytes(bytes[4 * j..4 * j + 4].try_into().unwrap()))
+                .collect::<Vec<_>>();
+
+            let libfunc = match i == blocks.len() - 1 {
+                true => "blake2s_finalize",
+                false => "blake2s_compress",
+            };
+            let byte_count = (64 * i + block.len()) as u32;
+            let (branch, results) = run_libfunc(
+                TYPES,
+                libfunc,
+                vec![state, Value::U32(byte_count), words(&message)],
+            );
+            assert_eq!(branch, 0);
+            [state] = results.try_into().unwrap();
+        }
+
+        let Value::Struct(state) = state else {
+            panic!()
+        };
+        let mut digest = [0; 32];
+        for (chunk, word) in digest.chunks_mut(4).zip(state) {
+            let Value::U32(word) = word else { panic!() };
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    #[test]
+    fn test_blake2s_in_vm() {
+        assert_eq!(blake2s_in_vm(b"abc"), ABC_DIGEST);
+        assert_eq!(blake2s_in_vm(&two_blocks()), TWO_BLOCKS_DIGEST);
+    }
+}
@@ -11,6 +11,7 @@ use cairo_lang_sierra::{
 };
 use num_bigint::BigInt;
 use smallvec::smallvec;
+use starknet_crypto::Felt;
 
 pub fn eval(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
@@ -39,7 +40,13 @@ pub fn eval_downcast(
         Value::U32(value) => BigInt::from(value),
         Value::U16(value) => BigInt::from(value),
         Value::U8(value) => BigInt::from(value),
-        _ => todo!(),
+        Value::I128(value) => BigInt::from(value),
+        Value::I64(value) => BigInt::from(value),
+        Value::I32(value) => BigInt::from(value),
+        Value::I16(value) => BigInt::from(value),
+        Value::I8(value) => BigInt::from(value),
+        Value::Felt(value) => felt_to_bigint(value),
+        _ => panic!(),
     };
 
     let range = info.to_range.lower.clone()..info.to_range.upper.clone();
@@ -50,14 +57,21 @@ pub fn eval_downcast(
                 range_check,
                 match registry.get_type(&info.to_ty).unwrap() {
                     CoreTypeConcrete::Sint8(_) => Value::I8(value.try_into().unwrap()),
+                    CoreTypeConcrete::Sint16(_) => Value::I16(value.try_into().unwrap()),
+                    CoreTypeConcrete::Sint32(_) => Value::I32(value.try_into().unwrap()),
+                    CoreTypeConcrete::Sint64(_) => Value::I64(value.try_into().unwrap()),
                     CoreTypeConcrete::Sint128(_) => Value::I128(value.try_into().unwrap()),
                     CoreTypeConcrete::Uint8(_) => Value::U8(value.try_into().unwrap()),
                     CoreTypeConcrete::Uint16(_) => Value::U16(value.try_into().unwrap()),
                     CoreTypeConcrete::Uint32(_) => Value::U32(value.try_into().unwrap()),
                     CoreTypeConcrete::Uint64(_) => Value::U64(value.try_into().unwrap()),
                     CoreTypeConcrete::Uint128(_) => Value::U128(value.try_into().unwrap()),
-                    CoreTypeConcrete::BoundedInt(_) => Value::BoundedInt { range, value },
-                    x => todo!("{:?}", x.info()),
+                    CoreTypeConcrete::Felt252(_) => Value::Felt(value.into()),
+                    CoreTypeConcrete::BoundedInt(info) => Value::BoundedInt {
+                        range: info.range.lower.clone()..info.range.upper.clone(),
+                        value,
+                    },
+                    x => panic!("{:?}", x.info()),
                 }
             ],
         )
@@ -66,6 +80,17 @@ pub fn eval_downcast(
     }
 }
 
+/// The integer a felt stands for in Sierra, whose felt252 range is `[1 - P, P)`: the ones above
+/// `P / 2` are negative.
+fn felt_to_bigint(value: Felt) -> BigInt {
+    let negated = -value;
+    if negated < value {
+        -negated.to_bigint()
+    } else {
+        value.to_bigint()
+    }
+}
+
 pub fn eval_upcast(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     info: &SignatureOnlyConcreteLibfunc,
@@ -80,7 +105,13 @@ pub fn eval_upcast(
         Value::U32(value) => BigInt::from(value),
         Value::U16(value) => BigInt::from(value),
         Value::U8(value) => BigInt::from(value),
-        _ => todo!(),
+        Value::I128(value) => BigInt::from(value),
+        Value::I64(value) => BigInt::from(value),
+        Value::I32(value) => BigInt::from(value),
+        Value::I16(value) => BigInt::from(value),
+        Value::I8(value) => BigInt::from(value),
+        Value::Felt(value) => felt_to_bigint(value),
+        _ => panic!(),
     };
 
     EvalAction::NormalBranch(
@@ -98,10 +129,166 @@ pub fn eval_upcast(
             CoreTypeConcrete::Uint64(_) => Value::U64(value.try_into().unwrap()),
             CoreTypeConcrete::Uint128(_) => Value::U128(value.try_into().unwrap()),
             CoreTypeConcrete::Felt252(_) => Value::Felt(value.into()),
-            CoreTypeConcrete::Sint16(_) => todo!("Sint16"),
-            CoreTypeConcrete::Sint64(_) => todo!("Sint64"),
-            CoreTypeConcrete::BoundedInt(_) => todo!("BoundedInt"),
-            _ => todo!(),
+            CoreTypeConcrete::Sint16(_) => Value::I16(value.try_into().unwrap()),
+            CoreTypeConcrete::Sint64(_) => Value::I64(value.try_into().unwrap()),
+            CoreTypeConcrete::BoundedInt(info) => Value::BoundedInt {
+                range: info.range.lower.clone()..info.range.upper.clone(),
+                value,
+            },
+            _ => panic!(),
         }],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_libfunc, Value};
+    use num_bigint::BigInt;
+
+    const TYPES: &str = r#"
+        type RangeCheck = RangeCheck;
+        type u8 = u8;
+        type u16 = u16;
+        type u32 = u32;
+        type u64 = u64;
+        type u128 = u128;
+        type i8 = i8;
+        type i16 = i16;
+        type i32 = i32;
+        type i64 = i64;
+        type i128 = i128;
+        type BoundedInt<-1, 300> = BoundedInt<-1, 300>;
+    "#;
+
+    /// The integer types declared in `TYPES` and their inclusive bounds.
+    fn int_types() -> [(&'static str, BigInt, BigInt); 11] {
+        [
+            ("u8", u8::MIN.into(), u8::MAX.into()),
+            ("u16", u16::MIN.into(), u16::MAX.into()),
+            ("u32", u32::MIN.into(), u32::MAX.into()),
+            ("u64", u64::MIN.into(), u64::MAX.into()),
+            ("u128", u128::MIN.into(), u128::MAX.into()),
+            ("i8", i8::MIN.into(), i8::MAX.into()),
+            ("i16", i16::MIN.into(), i16::MAX.into()),
+            ("i32", i32::MIN.into(), i32::MAX.into()),
+            ("i64", i64::MIN.into(), i64::MAX.into()),
+            ("i128", i128::MIN.into(), i128::MAX.into()),
+            ("BoundedInt<-1, 300>", (-1).into(), 300.into()),
+        ]
+    }
+
+    fn int_value(ty: &str, value: &BigInt) -> Value {
+        let value = value.clone();
+        match ty {
+            "u8" => Value::U8(value.try_into().unwrap()),
+            "u16" => Value::U16(value.try_into().unwrap()),
+            "u32" => Value::U32(value.try_into().unwrap()),
+            "u64" => Value::U64(value.try_into().unwrap()),
+            "u128" => Value::U128(value.try_into().unwrap()),
+            "i8" => Value::I8(value.try_into().unwrap()),
+            "i16" => Value::I16(value.try_into().unwrap()),
+            "i32" => Value::I32(value.try_into().unwrap()),
+            "i64" => Value::I64(value.try_into().unwrap()),
+            "i128" => Value::I128(value.try_into().unwrap()),
+            _ => Value::BoundedInt {
+                range: BigInt::from(-1)..BigInt::from(301),
+                value,
+            },
+        }
+    }
+
+    /// Casts the bounds of every type, and the values around the bounds of the target type, between
+    /// every pair of types.
+    #[test]
+    fn test_casts_between_every_pair() {
+        for (from, from_min, from_max) in int_types() {
+            for (to, to_min, to_max) in int_types() {
+                let values = [
+                    from_min.clone(),
+                    from_max.clone(),
+                    &to_min - 1,
+                    to_min.clone(),
+                    to_max.clone(),
+                    &to_max + 1,
+                    BigInt::from(-1),
+                    BigInt::from(0),
+                ];
+                let is_upcast = to_min <= from_min && from_max <= to_max;
+
+                for value in values
+                    .iter()
+                    .filter(|value| (&from_min..=&from_max).contains(value))
+                {
+                    let fits = (&to_min..=&to_max).contains(&value);
+                    let expected = match fits {
+                        true => (0, vec![Value::Unit, int_value(to, value)]),
+                        false => (1, vec![Value::Unit]),
+                    };
+                    assert_eq!(
+                        run_libfunc(
+                            TYPES,
+                            &format!("downcast<{from}, {to}>"),
+                            vec![int_value(from, value)]
+                        ),
+                        expected,
+                        "downcast<{from}, {to}>({value})"
+                    );
+
+                    if is_upcast {
+                        assert_eq!(
+                            run_libfunc(
+                                TYPES,
+                                &format!("upcast<{from}, {to}>"),
+                                vec![int_value(from, value)]
+                            ),
+                            (0, vec![int_value(to, value)]),
+                            "upcast<{from}, {to}>({value})"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_downcast_felt252() {
+        let types = r#"
+            type RangeCheck = RangeCheck;
+            type felt252 = felt252;
+            type i8 = i8;
+            type BoundedInt<-10, 10> = BoundedInt<-10, 10>;
+        "#;
+        let downcast = |to: &str, value: i64| {
+            run_libfunc(
+                types,
+                &format!("downcast<felt252, {to}>"),
+                vec![Value::Felt(value.into())],
+            )
+        };
+        let bounded_int = |value: i64| Value::BoundedInt {
+            range: BigInt::from(-10)..BigInt::from(11),
+            value: value.into(),
+        };
+
+        // Felts above `P / 2` are negative.
+        assert_eq!(downcast("i8", 127), (0, vec![Value::Unit, Value::I8(127)]));
+        assert_eq!(downcast("i8", -1), (0, vec![Value::Unit, Value::I8(-1)]));
+        assert_eq!(
+            downcast("i8", -128),
+            (0, vec![Value::Unit, Value::I8(-128)])
+        );
+        assert_eq!(downcast("i8", 128), (1, vec![Value::Unit]));
+        assert_eq!(downcast("i8", -129), (1, vec![Value::Unit]));
+
+        assert_eq!(
+            downcast("BoundedInt<-10, 10>", -10),
+            (0, vec![Value::Unit, bounded_int(-10)])
+        );
+        assert_eq!(
+            downcast("BoundedInt<-10, 10>", 10),
+            (0, vec![Value::Unit, bounded_int(10)])
+        );
+        assert_eq!(downcast("BoundedInt<-10, 10>", -11), (1, vec![Value::Unit]));
+        assert_eq!(downcast("BoundedInt<-10, 10>", 11), (1, vec![Value::Unit]));
+    }
+}
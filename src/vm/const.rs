@@ -6,6 +6,7 @@ use cairo_lang_sierra::{
             ConstAsBoxConcreteLibfunc, ConstAsImmediateConcreteLibfunc, ConstConcreteLibfunc,
         },
         core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        starknet::StarknetTypeConcrete,
     },
     ids::ConcreteTypeId,
     program::GenericArg,
@@ -94,7 +95,7 @@ fn inner(
             _ => unreachable!(),
         },
         CoreTypeConcrete::Sint64(_) => match inner_data {
-            [GenericArg::Value(value)] => Value::U64(value.try_into().unwrap()),
+            [GenericArg::Value(value)] => Value::I64(value.try_into().unwrap()),
             _ => unreachable!(),
         },
         CoreTypeConcrete::Sint32(_) => match inner_data {
@@ -160,6 +161,84 @@ fn inner(
 
             Value::Struct(fields)
         }
-        _ => todo!("{}", type_id),
+        CoreTypeConcrete::Enum(_) => match inner_data {
+            [GenericArg::Value(index), GenericArg::Type(payload_ty)] => {
+                match registry.get_type(payload_ty).unwrap() {
+                    CoreTypeConcrete::Const(info) => Value::Enum {
+                        self_ty: type_id.clone(),
+                        index: index.try_into().unwrap(),
+                        payload: Box::new(inner(registry, &info.inner_ty, &info.inner_data)),
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        },
+        CoreTypeConcrete::Bytes31(_) => match inner_data {
+            [GenericArg::Value(value)] => Value::Bytes31(value.into()),
+            _ => unreachable!(),
+        },
+        CoreTypeConcrete::Starknet(
+            StarknetTypeConcrete::ContractAddress(_) | StarknetTypeConcrete::ClassHash(_),
+        ) => match inner_data {
+            [GenericArg::Value(value)] => Value::Felt(value.into()),
+            _ => unreachable!(),
+        },
+        _ => unreachable!("{} can't be a constant", type_id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_test_program, Value};
+    use cairo_lang_sierra::{ids::ConcreteTypeId, ProgramParser};
+    use starknet_crypto::Felt;
+
+    #[test]
+    fn test_const_as_immediate() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type i64 = i64;
+                    type u8 = u8;
+                    type bytes31 = bytes31;
+                    type ContractAddress = ContractAddress;
+                    type E = Enum<ut@E, i64, u8>;
+                    type Const<i64, -5> = Const<i64, -5>;
+                    type Const<u8, 7> = Const<u8, 7>;
+                    type Const<bytes31, 256> = Const<bytes31, 256>;
+                    type Const<ContractAddress, 3> = Const<ContractAddress, 3>;
+                    type Const<E, 1, Const<u8, 7>> = Const<E, 1, Const<u8, 7>>;
+
+                    libfunc const_as_immediate<Const<i64, -5>> = const_as_immediate<Const<i64, -5>>;
+                    libfunc const_as_immediate<Const<bytes31, 256>> = const_as_immediate<Const<bytes31, 256>>;
+                    libfunc const_as_immediate<Const<ContractAddress, 3>> = const_as_immediate<Const<ContractAddress, 3>>;
+                    libfunc const_as_immediate<Const<E, 1, Const<u8, 7>>> = const_as_immediate<Const<E, 1, Const<u8, 7>>>;
+
+                    const_as_immediate<Const<i64, -5>>() -> ([0]);
+                    const_as_immediate<Const<bytes31, 256>>() -> ([1]);
+                    const_as_immediate<Const<ContractAddress, 3>>() -> ([2]);
+                    const_as_immediate<Const<E, 1, Const<u8, 7>>>() -> ([3]);
+                    return([0], [1], [2], [3]);
+
+                    main@0() -> (i64, bytes31, ContractAddress, E);
+                "#,
+            )
+            .unwrap();
+
+        let result = run_test_program(program);
+        assert_eq!(
+            result,
+            [
+                Value::I64(-5),
+                Value::Bytes31(Felt::from(256)),
+                Value::Felt(Felt::from(3)),
+                Value::Enum {
+                    self_ty: ConcreteTypeId::from_string("E"),
+                    index: 1,
+                    payload: Box::new(Value::U8(7)),
+                },
+            ]
+        );
     }
 }
@@ -0,0 +1,92 @@
+use super::EvalAction;
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType},
+        coupon::CouponConcreteLibfunc,
+        function_call::SignatureAndFunctionConcreteLibfunc,
+    },
+    program_registry::ProgramRegistry,
+};
+use smallvec::smallvec;
+
+// The cost of a coupon is part of the gas metadata of the statement that buys or refunds it, so
+// it's withdrawn or redeposited by the surrounding gas libfuncs.
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &CouponConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    match selector {
+        CouponConcreteLibfunc::Buy(info) => eval_buy(registry, info, args),
+        CouponConcreteLibfunc::Refund(info) => eval_refund(registry, info, args),
+    }
+}
+
+pub fn eval_buy(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndFunctionConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [] = args.try_into().unwrap();
+
+    EvalAction::NormalBranch(0, smallvec![Value::Coupon(info.function.id.clone())])
+}
+
+pub fn eval_refund(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndFunctionConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::Coupon(function_id)]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+    assert_eq!(function_id, info.function.id);
+
+    EvalAction::NormalBranch(0, smallvec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_test_vm, Value};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_coupon_call() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Coupon = Coupon<user@double>;
+
+                    libfunc coupon_buy<Coupon> = coupon_buy<Coupon>;
+                    libfunc coupon_refund<Coupon> = coupon_refund<Coupon>;
+                    libfunc coupon_call<user@double> = coupon_call<user@double>;
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    coupon_buy<Coupon>() -> ([1]);
+                    coupon_buy<Coupon>() -> ([2]);
+                    coupon_refund<Coupon>([2]) -> ();
+                    store_temp<felt252>([0]) -> ([0]);
+                    coupon_call<user@double>([0], [1]) -> ([3]);
+                    store_temp<felt252>([3]) -> ([3]);
+                    return([3]);
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    store_temp<felt252>([2]) -> ([2]);
+                    return([2]);
+
+                    main@0([0]: felt252) -> (felt252);
+                    double@7([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+
+        let (vm, values) = run_test_vm(program, 0, vec![Value::Felt(21.into())]);
+        // Every statement of both functions runs once.
+        assert_eq!(vm.execution_resources().n_steps, 11);
+        assert_eq!(values, [Value::Felt(42.into())]);
+    }
+}
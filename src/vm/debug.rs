@@ -0,0 +1,102 @@
+use super::EvalAction;
+use crate::{panic::format_for_debug, Value};
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType},
+        debug::DebugConcreteLibfunc,
+        lib_func::SignatureOnlyConcreteLibfunc,
+    },
+    program_registry::ProgramRegistry,
+};
+use smallvec::smallvec;
+
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &DebugConcreteLibfunc,
+    args: Vec<Value>,
+    output: &mut Vec<String>,
+) -> EvalAction {
+    match selector {
+        DebugConcreteLibfunc::Print(info) => eval_print(registry, info, args, output),
+    }
+}
+
+pub fn eval_print(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+    output: &mut Vec<String>,
+) -> EvalAction {
+    let [Value::Array { data, .. }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    let felts = data
+        .into_iter()
+        .map(|value| match value {
+            Value::Felt(value) => value,
+            _ => panic!(),
+        })
+        .collect::<Vec<_>>();
+    output.push(format_for_debug(&felts));
+
+    EvalAction::NormalBranch(0, smallvec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        output::{OutputSink, PrintedOutput},
+        starknet::StubSyscallHandler,
+        VirtualMachine,
+    };
+    use cairo_lang_sierra::{program::StatementIdx, ProgramParser};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_print() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+                    type Array<felt252> = Array<felt252>;
+                    type Unit = Struct<ut@Tuple>;
+
+                    libfunc array_new<felt252> = array_new<felt252>;
+                    libfunc array_append<felt252> = array_append<felt252>;
+                    libfunc felt252_const<26982> = felt252_const<26982>;
+                    libfunc felt252_const<255> = felt252_const<255>;
+                    libfunc print = print;
+                    libfunc struct_construct<Unit> = struct_construct<Unit>;
+                    libfunc store_temp<Unit> = store_temp<Unit>;
+
+                    array_new<felt252>() -> ([0]);
+                    felt252_const<26982>() -> ([1]);
+                    array_append<felt252>([0], [1]) -> ([0]);
+                    felt252_const<255>() -> ([2]);
+                    array_append<felt252>([0], [2]) -> ([0]);
+                    print([0]) -> ();
+                    struct_construct<Unit>() -> ([3]);
+                    store_temp<Unit>([3]) -> ([3]);
+                    return([3]);
+
+                    main@0() -> (Unit);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.set_output_sink(OutputSink::Capture);
+        vm.call_program(&program.funcs[0], 0, []);
+        let trace = vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        assert_eq!(
+            trace.output,
+            [PrintedOutput {
+                statement_idx: StatementIdx(5),
+                text: "[DEBUG]\t0x6966 ('if')\n[DEBUG]\t0xff\n".to_string(),
+            }]
+        );
+    }
+}
@@ -10,6 +10,7 @@ use cairo_lang_sierra::{
 };
 use num_traits::identities::Zero;
 use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use smallvec::smallvec;
 use starknet_crypto::Felt;
 use starknet_curve::curve_params::BETA;
@@ -23,6 +24,7 @@ pub fn eval(
     registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     selector: &EcConcreteLibfunc,
     args: Vec<Value>,
+    rng: &mut ChaCha8Rng,
 ) -> EvalAction {
     match selector {
         EcConcreteLibfunc::IsZero(info) => eval_is_zero(registry, info, args),
@@ -30,14 +32,30 @@ pub fn eval(
         EcConcreteLibfunc::StateAdd(info) => eval_state_add(registry, info, args),
         EcConcreteLibfunc::TryNew(info) => eval_new(registry, info, args),
         EcConcreteLibfunc::StateFinalize(info) => eval_state_finalize(registry, info, args),
-        EcConcreteLibfunc::StateInit(info) => eval_state_init(registry, info, args),
+        EcConcreteLibfunc::StateInit(info) => eval_state_init(registry, info, args, rng),
         EcConcreteLibfunc::StateAddMul(info) => eval_state_add_mul(registry, info, args),
         EcConcreteLibfunc::PointFromX(info) => eval_point_from_x(registry, info, args),
         EcConcreteLibfunc::UnwrapPoint(info) => eval_unwrap_point(registry, info, args),
-        EcConcreteLibfunc::Zero(_) => todo!(),
+        EcConcreteLibfunc::Zero(info) => eval_zero(registry, info, args),
     }
 }
 
+pub fn eval_zero(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [] = args.try_into().unwrap();
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![Value::EcPoint {
+            x: Felt::ZERO,
+            y: Felt::ZERO,
+        }],
+    )
+}
+
 pub fn eval_is_zero(
     _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     _info: &SignatureOnlyConcreteLibfunc,
@@ -111,8 +129,9 @@ pub fn eval_state_init(
     _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     _info: &SignatureOnlyConcreteLibfunc,
     _args: Vec<Value>,
+    rng: &mut ChaCha8Rng,
 ) -> EvalAction {
-    let state = random_ec_point();
+    let state = random_ec_point(rng);
 
     EvalAction::NormalBranch(
         0,
@@ -243,9 +262,8 @@ pub fn eval_point_from_x(
     }
 }
 
-fn random_ec_point() -> AffinePoint {
+fn random_ec_point(rng: &mut ChaCha8Rng) -> AffinePoint {
     // https://github.com/starkware-libs/cairo/blob/aaad921bba52e729dc24ece07fab2edf09ccfa15/crates/cairo-lang-runner/src/casm_run/mod.rs#L1802
-    let mut rng = rand::thread_rng();
     let (random_x, random_y) = loop {
         // Randominzing 31 bytes to make sure is in range.
         let x_bytes: [u8; 31] = rng.gen();
@@ -258,3 +276,79 @@ fn random_ec_point() -> AffinePoint {
 
     AffinePoint::new(random_x, random_y).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        starknet::StubSyscallHandler, test_utils::run_test_program, Value, VirtualMachine,
+    };
+    use cairo_lang_sierra::ProgramParser;
+    use starknet_crypto::Felt;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_zero() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type EcPoint = EcPoint;
+
+                    libfunc ec_point_zero = ec_point_zero;
+
+                    ec_point_zero() -> ([0]);
+                    return([0]);
+
+                    main@0() -> (EcPoint);
+                "#,
+            )
+            .unwrap();
+
+        let result = run_test_program(program);
+        assert_eq!(
+            result,
+            [Value::EcPoint {
+                x: Felt::ZERO,
+                y: Felt::ZERO,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_state_init_is_deterministic() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type EcState = EcState;
+
+                    libfunc ec_state_init = ec_state_init;
+
+                    ec_state_init() -> ([0]);
+                    return([0]);
+
+                    main@0() -> (EcState);
+                "#,
+            )
+            .unwrap();
+
+        let run = |seed| {
+            let mut vm = VirtualMachine::new(Arc::new(program.clone()));
+            vm.set_seed(seed);
+            vm.call_program(&program.funcs[0], 0, []);
+            vm.run_with_trace(&mut StubSyscallHandler::default())
+                .states
+                .last()
+                .unwrap()
+                .items
+                .values()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            run_test_program(program.clone()),
+            run_test_program(program.clone())
+        );
+        assert_eq!(run(0), run_test_program(program.clone()));
+        assert_ne!(run(0), run(1));
+    }
+}
@@ -108,15 +108,13 @@ pub fn eval_snapshot_match(
         panic!()
     };
 
-    let ty = registry
-        .get_type(&info.signature.param_signatures[0].ty)
-        .unwrap();
-
-    if let CoreTypeConcrete::Snapshot(inner) = ty {
-        assert_eq!(inner.ty, self_ty);
-    } else {
-        panic!("expected snapshot type")
-    }
+    // The snapshot of a duplicatable enum is the enum itself.
+    let param_ty = &info.signature.param_signatures[0].ty;
+    let enum_ty = match registry.get_type(param_ty).unwrap() {
+        CoreTypeConcrete::Snapshot(inner) => &inner.ty,
+        _ => param_ty,
+    };
+    assert_eq!(*enum_ty, self_ty);
 
     assert!(payload.is(
         registry,
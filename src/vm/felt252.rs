@@ -31,20 +31,28 @@ pub fn eval_operation(
     info: &Felt252BinaryOperationConcrete,
     args: Vec<Value>,
 ) -> EvalAction {
-    let res = match info {
+    let (operator, lhs, rhs) = match info {
         Felt252BinaryOperationConcrete::WithVar(info) => {
             let [Value::Felt(lhs), Value::Felt(rhs)]: [Value; 2] = args.try_into().unwrap() else {
                 panic!()
             };
 
-            match info.operator {
-                Felt252BinaryOperator::Add => lhs + rhs,
-                Felt252BinaryOperator::Sub => lhs - rhs,
-                Felt252BinaryOperator::Mul => lhs * rhs,
-                Felt252BinaryOperator::Div => lhs.field_div(&rhs.try_into().unwrap()),
-            }
+            (info.operator, lhs, rhs)
         }
-        Felt252BinaryOperationConcrete::WithConst(_info) => todo!(),
+        Felt252BinaryOperationConcrete::WithConst(info) => {
+            let [Value::Felt(lhs)]: [Value; 1] = args.try_into().unwrap() else {
+                panic!()
+            };
+
+            (info.operator, lhs, Felt::from(info.c.clone()))
+        }
+    };
+
+    let res = match operator {
+        Felt252BinaryOperator::Add => lhs + rhs,
+        Felt252BinaryOperator::Sub => lhs - rhs,
+        Felt252BinaryOperator::Mul => lhs * rhs,
+        Felt252BinaryOperator::Div => lhs.field_div(&rhs.try_into().unwrap()),
     };
 
     EvalAction::NormalBranch(0, smallvec![Value::Felt(res)])
@@ -73,3 +81,36 @@ pub fn eval_felt_is_zero(
         EvalAction::NormalBranch(1, smallvec![Value::Felt(value)])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_libfunc, Value};
+    use starknet_crypto::Felt;
+
+    #[test]
+    fn test_operation_with_const() {
+        for operation in ["add", "sub", "mul", "div"] {
+            for rhs in [-2, 1, 4, 15] {
+                for lhs in [Felt::ZERO, Felt::ONE, Felt::from(10), Felt::MAX] {
+                    let rhs_felt = Felt::from(rhs);
+                    let expected = match operation {
+                        "add" => lhs + rhs_felt,
+                        "sub" => lhs - rhs_felt,
+                        "mul" => lhs * rhs_felt,
+                        _ => lhs.field_div(&rhs_felt.try_into().unwrap()),
+                    };
+
+                    assert_eq!(
+                        run_libfunc(
+                            "type felt252 = felt252;",
+                            &format!("felt252_{operation}_const<{rhs}>"),
+                            vec![Value::Felt(lhs)]
+                        ),
+                        (0, vec![Value::Felt(expected)]),
+                        "felt252_{operation}_const<{rhs}>({lhs})"
+                    );
+                }
+            }
+        }
+    }
+}
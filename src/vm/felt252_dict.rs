@@ -1,11 +1,12 @@
 use super::EvalAction;
-use crate::Value;
+use crate::{DictAccess, Value};
 use cairo_lang_sierra::{
     extensions::{
         core::{CoreLibfunc, CoreType, CoreTypeConcrete},
         felt252_dict::Felt252DictConcreteLibfunc,
         lib_func::SignatureOnlyConcreteLibfunc,
     },
+    ids::ConcreteTypeId,
     program_registry::ProgramRegistry,
 };
 use cairo_lang_sierra_gas::core_libfunc_cost::{
@@ -49,18 +50,18 @@ pub fn eval_new(
             Value::FeltDict {
                 ty: ty.clone(),
                 data: HashMap::new(),
-                count: 0
+                log: Vec::new(),
             },
         ],
     )
 }
 
 pub fn eval_squash(
-    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     _info: &SignatureOnlyConcreteLibfunc,
     args: Vec<Value>,
 ) -> EvalAction {
-    let [range_check @ Value::Unit, Value::U64(gas_builtin), segment_arena @ Value::Unit, Value::FeltDict { ty, data, count }]: [Value; 4] =
+    let [range_check @ Value::Unit, Value::U64(gas_builtin), segment_arena @ Value::Unit, Value::FeltDict { ty, data, log }]: [Value; 4] =
         args.try_into().unwrap()
     else {
         panic!();
@@ -69,7 +70,18 @@ pub fn eval_squash(
     const DICT_GAS_REFUND_PER_ACCESS: u64 =
         (DICT_SQUASH_UNIQUE_KEY_COST.cost() - DICT_SQUASH_REPEATED_ACCESS_COST.cost()) as u64;
 
-    let refund = count.saturating_sub(data.len() as u64) * DICT_GAS_REFUND_PER_ACCESS;
+    let squashed = squash_log(registry, &ty, &log);
+    for access in &squashed {
+        assert_eq!(
+            data.get(&access.key),
+            Some(&access.new_value),
+            "dict value of key {} doesn't match its access log",
+            access.key
+        );
+    }
+    assert_eq!(data.len(), squashed.len());
+
+    let refund = (log.len() - squashed.len()) as u64 * DICT_GAS_REFUND_PER_ACCESS;
     let new_gas_builtin = gas_builtin + refund;
 
     EvalAction::NormalBranch(
@@ -78,7 +90,129 @@ pub fn eval_squash(
             range_check,
             Value::U64(new_gas_builtin),
             segment_arena,
-            Value::FeltDict { ty, data, count }
+            Value::FeltSquashedDict {
+                ty,
+                entries: squashed
+            }
         ],
     )
 }
+
+/// Validate an access log, checking that every access starts from the value left by the previous
+/// access to its key, and compact it into a single access per key, sorted by key.
+fn squash_log(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    ty: &ConcreteTypeId,
+    log: &[DictAccess],
+) -> Vec<DictAccess> {
+    let mut squashed = Vec::<DictAccess>::new();
+    let mut indices = HashMap::new();
+    for access in log {
+        match indices.get(&access.key) {
+            Some(&index) => {
+                let squashed_access: &mut DictAccess = &mut squashed[index];
+                assert_eq!(
+                    access.prev_value, squashed_access.new_value,
+                    "invalid access to dict key {}",
+                    access.key
+                );
+                squashed_access.new_value = access.new_value.clone();
+            }
+            None => {
+                assert_eq!(
+                    access.prev_value,
+                    Value::default_for_type(registry, ty),
+                    "invalid first access to dict key {}",
+                    access.key
+                );
+                indices.insert(access.key, squashed.len());
+                squashed.push(access.clone());
+            }
+        }
+    }
+    squashed.sort_by_key(|access| access.key);
+
+    squashed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::run_test_vm;
+    use cairo_lang_sierra::ProgramParser;
+    use cairo_lang_sierra_gas::core_libfunc_cost::DICT_SQUASH_FIXED_COST;
+
+    #[test]
+    fn test_dict_access_log() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type GasBuiltin = GasBuiltin;
+                    type SegmentArena = SegmentArena;
+                    type felt252 = felt252;
+                    type Felt252Dict<felt252> = Felt252Dict<felt252>;
+                    type Felt252DictEntry<felt252> = Felt252DictEntry<felt252>;
+                    type SquashedFelt252Dict<felt252> = SquashedFelt252Dict<felt252>;
+
+                    libfunc felt252_dict_new<felt252> = felt252_dict_new<felt252>;
+                    libfunc felt252_dict_entry_get<felt252> = felt252_dict_entry_get<felt252>;
+                    libfunc felt252_dict_entry_finalize<felt252> = felt252_dict_entry_finalize<felt252>;
+                    libfunc felt252_dict_squash<felt252> = felt252_dict_squash<felt252>;
+                    libfunc felt252_const<1> = felt252_const<1>;
+                    libfunc felt252_const<5> = felt252_const<5>;
+                    libfunc drop<felt252> = drop<felt252>;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    felt252_dict_new<felt252>([2]) -> ([2], [3]);
+                    felt252_const<1>() -> ([4]);
+                    felt252_dict_entry_get<felt252>([3], [4]) -> ([5], [6]);
+                    drop<felt252>([6]) -> ();
+                    felt252_const<5>() -> ([7]);
+                    felt252_dict_entry_finalize<felt252>([5], [7]) -> ([3]);
+                    felt252_const<1>() -> ([4]);
+                    felt252_dict_entry_get<felt252>([3], [4]) -> ([5], [6]);
+                    dup<felt252>([6]) -> ([6], [8]);
+                    felt252_dict_entry_finalize<felt252>([5], [6]) -> ([3]);
+                    felt252_dict_squash<felt252>([0], [1], [2], [3]) -> ([0], [1], [2], [3]);
+                    store_temp<felt252>([8]) -> ([8]);
+                    return([0], [1], [2], [3], [8]);
+
+                    main@0([0]: RangeCheck, [1]: GasBuiltin, [2]: SegmentArena) -> (RangeCheck, GasBuiltin, SegmentArena, SquashedFelt252Dict<felt252>, felt252);
+                "#,
+            )
+            .unwrap();
+
+        let (vm, values) = run_test_vm(program, 0, Vec::new());
+        let [_, Value::U64(gas), _, Value::FeltSquashedDict { entries, .. }, value] = &values[..]
+        else {
+            panic!()
+        };
+
+        // The second read sees the value written by the first access.
+        assert_eq!(*value, Value::Felt(5.into()));
+        assert_eq!(
+            entries,
+            &[DictAccess {
+                key: 1.into(),
+                prev_value: Value::Felt(0.into()),
+                new_value: Value::Felt(5.into()),
+            }]
+        );
+        assert_eq!(
+            *gas,
+            (DICT_SQUASH_UNIQUE_KEY_COST.cost() - DICT_SQUASH_REPEATED_ACCESS_COST.cost()) as u64
+        );
+
+        // The squash is charged for one unique key and one repeated access.
+        let resources = vm.execution_resources();
+        assert_eq!(
+            resources.range_check,
+            (DICT_SQUASH_FIXED_COST.range_checks
+                + DICT_SQUASH_UNIQUE_KEY_COST.range_checks
+                + DICT_SQUASH_REPEATED_ACCESS_COST.range_checks) as u64
+        );
+        assert_eq!(resources.segment_arena, 2);
+    }
+}
@@ -1,5 +1,5 @@
 use super::EvalAction;
-use crate::Value;
+use crate::{DictAccess, Value};
 use cairo_lang_sierra::{
     extensions::{
         core::{CoreLibfunc, CoreType},
@@ -26,32 +26,21 @@ pub fn eval_get(
     info: &SignatureAndTypeConcreteLibfunc,
     args: Vec<Value>,
 ) -> EvalAction {
-    let [Value::FeltDict {
-        ty,
-        mut data,
-        count,
-    }, Value::Felt(key)]: [Value; 2] = args.try_into().unwrap()
+    let [Value::FeltDict { ty, data, log }, Value::Felt(key)]: [Value; 2] =
+        args.try_into().unwrap()
     else {
         panic!()
     };
     assert_eq!(info.ty, ty);
 
-    let default_value = Value::default_for_type(registry, &info.ty);
-    data.insert(key, default_value.clone());
-
-    let count = count + 1;
+    let value = data
+        .get(&key)
+        .cloned()
+        .unwrap_or_else(|| Value::default_for_type(registry, &info.ty));
 
     EvalAction::NormalBranch(
         0,
-        smallvec![
-            Value::FeltDictEntry {
-                ty,
-                data,
-                count,
-                key
-            },
-            default_value,
-        ],
+        smallvec![Value::FeltDictEntry { ty, data, log, key }, value],
     )
 }
 
@@ -63,7 +52,7 @@ pub fn eval_finalize(
     let [Value::FeltDictEntry {
         ty,
         mut data,
-        count,
+        mut log,
         key,
     }, value]: [Value; 2] = args.try_into().unwrap()
     else {
@@ -72,7 +61,14 @@ pub fn eval_finalize(
     assert_eq!(info.ty, ty);
     assert!(value.is(registry, &ty));
 
-    data.insert(key, value);
+    let prev_value = data
+        .insert(key, value.clone())
+        .unwrap_or_else(|| Value::default_for_type(registry, &ty));
+    log.push(DictAccess {
+        key,
+        prev_value,
+        new_value: value,
+    });
 
-    EvalAction::NormalBranch(0, smallvec![Value::FeltDict { ty, data, count }])
+    EvalAction::NormalBranch(0, smallvec![Value::FeltDict { ty, data, log }])
 }
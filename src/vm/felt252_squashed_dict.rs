@@ -0,0 +1,126 @@
+use super::EvalAction;
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        lib_func::SignatureAndTypeConcreteLibfunc,
+        squashed_felt252_dict::SquashedFelt252DictConcreteLibfunc,
+    },
+    program_registry::ProgramRegistry,
+};
+use smallvec::smallvec;
+
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &SquashedFelt252DictConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    match selector {
+        SquashedFelt252DictConcreteLibfunc::IntoEntries(info) => {
+            eval_into_entries(registry, info, args)
+        }
+    }
+}
+
+/// Convert a squashed dict into an array of `(key, first value, last value)` tuples.
+pub fn eval_into_entries(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndTypeConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::FeltSquashedDict { ty, entries }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+    assert_eq!(info.ty, ty);
+
+    let type_info = registry
+        .get_type(&info.signature.branch_signatures[0].vars[0].ty)
+        .unwrap();
+    let tuple_ty = match type_info {
+        CoreTypeConcrete::Array(info) => &info.ty,
+        _ => unreachable!(),
+    };
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![Value::Array {
+            ty: tuple_ty.clone(),
+            data: entries
+                .into_iter()
+                .map(|access| Value::Struct(vec![
+                    Value::Felt(access.key),
+                    access.prev_value,
+                    access.new_value,
+                ]))
+                .collect(),
+        }],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_test_vm, Value};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_squashed_dict_entries() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type GasBuiltin = GasBuiltin;
+                    type SegmentArena = SegmentArena;
+                    type felt252 = felt252;
+                    type Felt252Dict<felt252> = Felt252Dict<felt252>;
+                    type Felt252DictEntry<felt252> = Felt252DictEntry<felt252>;
+                    type SquashedFelt252Dict<felt252> = SquashedFelt252Dict<felt252>;
+                    type Entry = Struct<ut@Tuple, felt252, felt252, felt252>;
+                    type Array<Entry> = Array<Entry>;
+
+                    libfunc felt252_dict_new<felt252> = felt252_dict_new<felt252>;
+                    libfunc felt252_dict_entry_get<felt252> = felt252_dict_entry_get<felt252>;
+                    libfunc felt252_dict_entry_finalize<felt252> = felt252_dict_entry_finalize<felt252>;
+                    libfunc felt252_dict_squash<felt252> = felt252_dict_squash<felt252>;
+                    libfunc squashed_felt252_dict_entries<felt252> = squashed_felt252_dict_entries<felt252>;
+                    libfunc felt252_const<1> = felt252_const<1>;
+                    libfunc felt252_const<3> = felt252_const<3>;
+                    libfunc felt252_const<5> = felt252_const<5>;
+                    libfunc felt252_const<7> = felt252_const<7>;
+                    libfunc drop<felt252> = drop<felt252>;
+                    libfunc store_temp<Array<Entry>> = store_temp<Array<Entry>>;
+
+                    felt252_dict_new<felt252>([2]) -> ([2], [3]);
+                    felt252_const<3>() -> ([4]);
+                    felt252_dict_entry_get<felt252>([3], [4]) -> ([5], [6]);
+                    drop<felt252>([6]) -> ();
+                    felt252_const<5>() -> ([7]);
+                    felt252_dict_entry_finalize<felt252>([5], [7]) -> ([3]);
+                    felt252_const<1>() -> ([4]);
+                    felt252_dict_entry_get<felt252>([3], [4]) -> ([5], [6]);
+                    drop<felt252>([6]) -> ();
+                    felt252_const<7>() -> ([7]);
+                    felt252_dict_entry_finalize<felt252>([5], [7]) -> ([3]);
+                    felt252_dict_squash<felt252>([0], [1], [2], [3]) -> ([0], [1], [2], [3]);
+                    squashed_felt252_dict_entries<felt252>([3]) -> ([3]);
+                    store_temp<Array<Entry>>([3]) -> ([3]);
+                    return([0], [1], [2], [3]);
+
+                    main@0([0]: RangeCheck, [1]: GasBuiltin, [2]: SegmentArena) -> (RangeCheck, GasBuiltin, SegmentArena, Array<Entry>);
+                "#,
+            )
+            .unwrap();
+
+        let (_, values) = run_test_vm(program, 0, Vec::new());
+        let Some(Value::Array { data, .. }) = values.last() else {
+            panic!()
+        };
+        let entry = |key: u64, value: u64| {
+            Value::Struct(vec![
+                Value::Felt(key.into()),
+                Value::Felt(0.into()),
+                Value::Felt(value.into()),
+            ])
+        };
+        assert_eq!(data, &[entry(1, 7), entry(3, 5)]);
+    }
+}
@@ -21,3 +21,17 @@ pub fn eval(
 
     EvalAction::FunctionCall(info.function.id.clone(), args.into_iter().collect())
 }
+
+/// Call a function whose cost has been paid in advance with a coupon, which is consumed.
+pub fn eval_coupon_call(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndFunctionConcreteLibfunc,
+    mut args: Vec<Value>,
+) -> EvalAction {
+    let Some(Value::Coupon(function_id)) = args.pop() else {
+        panic!()
+    };
+    assert_eq!(function_id, info.function.id);
+
+    eval(registry, info, args)
+}
@@ -1,6 +1,6 @@
 use super::EvalAction;
 use crate::{
-    gas::{BuiltinCosts, GasMetadata},
+    gas::{BuiltinCosts, GasMetadata, GasWallet},
     Value,
 };
 use cairo_lang_sierra::{
@@ -20,6 +20,7 @@ pub fn eval(
     args: Vec<Value>,
     gas: &GasMetadata,
     statement_idx: StatementIdx,
+    gas_wallet: &GasWallet,
     builtin_costs: BuiltinCosts,
 ) -> EvalAction {
     match selector {
@@ -29,14 +30,16 @@ pub fn eval(
         GasConcreteLibfunc::RedepositGas(info) => {
             eval_redeposit_gas(registry, info, args, gas, statement_idx, builtin_costs)
         }
-        GasConcreteLibfunc::GetAvailableGas(_) => todo!(),
+        GasConcreteLibfunc::GetAvailableGas(info) => eval_get_available_gas(registry, info, args),
         GasConcreteLibfunc::BuiltinWithdrawGas(info) => {
             eval_builtin_withdraw_gas(registry, info, args, gas, statement_idx)
         }
         GasConcreteLibfunc::GetBuiltinCosts(info) => {
             eval_get_builtin_costs(registry, info, args, builtin_costs)
         }
-        GasConcreteLibfunc::GetUnspentGas(_) => todo!(),
+        GasConcreteLibfunc::GetUnspentGas(info) => {
+            eval_get_unspent_gas(registry, info, args, gas_wallet, builtin_costs)
+        }
     }
 }
 
@@ -182,3 +185,148 @@ pub fn eval_get_builtin_costs(
 ) -> EvalAction {
     EvalAction::NormalBranch(0, smallvec![Value::BuiltinCosts(builtin_costs)])
 }
+
+pub fn eval_get_available_gas(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::U64(gas)]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    EvalAction::NormalBranch(0, smallvec![Value::U64(gas), Value::U128(gas.into())])
+}
+
+/// The gas counter plus the gas already charged for the current function that hasn't been
+/// consumed yet.
+pub fn eval_get_unspent_gas(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+    gas_wallet: &GasWallet,
+    builtin_costs: BuiltinCosts,
+) -> EvalAction {
+    let [Value::U64(gas)]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    let unspent_gas = gas as i128 + builtin_costs.wallet_gas(gas_wallet);
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![
+            Value::U64(gas),
+            Value::U128(unspent_gas.try_into().unwrap())
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{starknet::StubSyscallHandler, Value, VirtualMachine};
+    use cairo_lang_runner::{RunResultValue, SierraCasmRunner, StarknetState};
+    use cairo_lang_sierra::{program::Program, ProgramParser};
+    use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+    use std::sync::Arc;
+
+    fn unspent_gas_program() -> Program {
+        ProgramParser::new()
+            .parse(
+                r#"
+                    type GasBuiltin = GasBuiltin;
+                    type felt252 = felt252;
+                    type u128 = u128;
+
+                    libfunc felt252_const<1> = felt252_const<1>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+                    libfunc drop<felt252> = drop<felt252>;
+                    libfunc drop<u128> = drop<u128>;
+                    libfunc get_available_gas = get_available_gas;
+                    libfunc get_unspent_gas = get_unspent_gas;
+                    libfunc store_temp<GasBuiltin> = store_temp<GasBuiltin>;
+                    libfunc store_temp<u128> = store_temp<u128>;
+                    libfunc function_call<user@inner> = function_call<user@inner>;
+
+                    store_temp<GasBuiltin>([0]) -> ([0]);
+                    function_call<user@inner>([0]) -> ([0], [1]);
+                    drop<u128>([1]) -> ();
+                    get_unspent_gas([0]) -> ([0], [2]);
+                    get_available_gas([0]) -> ([0], [3]);
+                    drop<u128>([3]) -> ();
+                    store_temp<GasBuiltin>([0]) -> ([0]);
+                    store_temp<u128>([2]) -> ([2]);
+                    return([0], [2]);
+                    felt252_const<1>() -> ([1]);
+                    store_temp<felt252>([1]) -> ([1]);
+                    drop<felt252>([1]) -> ();
+                    get_unspent_gas([0]) -> ([0], [4]);
+                    store_temp<GasBuiltin>([0]) -> ([0]);
+                    store_temp<u128>([4]) -> ([4]);
+                    return([0], [4]);
+
+                    main@0([0]: GasBuiltin) -> (GasBuiltin, u128);
+                    inner@9([0]: GasBuiltin) -> (GasBuiltin, u128);
+                "#,
+            )
+            .unwrap()
+    }
+
+    fn run_unspent_gas_program(program: Arc<Program>, profile: bool) -> u128 {
+        let mut vm = VirtualMachine::new(program.clone());
+        if profile {
+            vm.enable_profiler();
+            vm.enable_gas_profiler();
+        }
+        let initial_gas = vm
+            .gas
+            .get_initial_available_gas(&program.funcs[0].id, Some(100000))
+            .unwrap();
+        vm.call_program(&program.funcs[0], initial_gas, []);
+        let trace = vm.run_with_trace(&mut StubSyscallHandler::default());
+
+        if profile {
+            assert_eq!(vm.profiler().unwrap().libfuncs["get_unspent_gas"], 2);
+        }
+
+        let Value::U128(unspent_gas) = trace.states.last().unwrap().items[&2] else {
+            panic!()
+        };
+        unspent_gas
+    }
+
+    #[test]
+    fn test_get_unspent_gas() {
+        let program = unspent_gas_program();
+        let runner = SierraCasmRunner::new(
+            program.clone(),
+            Some(MetadataComputationConfig::default()),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+        let expected = runner
+            .run_function_with_starknet_context(
+                &program.funcs[0],
+                vec![],
+                Some(100000),
+                StarknetState::default(),
+            )
+            .unwrap();
+
+        let unspent_gas = run_unspent_gas_program(Arc::new(program), false);
+        assert_eq!(
+            expected.value,
+            RunResultValue::Success(vec![unspent_gas.into()])
+        );
+    }
+
+    #[test]
+    fn test_get_unspent_gas_with_profilers() {
+        let program = Arc::new(unspent_gas_program());
+        assert_eq!(
+            run_unspent_gas_program(program.clone(), true),
+            run_unspent_gas_program(program, false)
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use cairo_lang_sierra::{
     extensions::{
         core::{CoreLibfunc, CoreType},
-        int::{signed128::Sint128Concrete, IntOperationConcreteLibfunc, IntOperator},
+        int::{
+            signed128::{Sint128Concrete, Sint128Traits},
+            IntConstConcreteLibfunc, IntOperationConcreteLibfunc, IntOperator,
+        },
         lib_func::SignatureOnlyConcreteLibfunc,
     },
     program_registry::ProgramRegistry,
@@ -20,7 +23,7 @@ pub fn eval(
     args: Vec<Value>,
 ) -> EvalAction {
     match selector {
-        Sint128Concrete::Const(_) => todo!("int128 const"),
+        Sint128Concrete::Const(info) => eval_const(registry, info, args),
         Sint128Concrete::Operation(info) => eval_operation(registry, info, args),
         Sint128Concrete::Equal(info) => eval_equal(registry, info, args),
         Sint128Concrete::ToFelt252(info) => eval_to_felt(registry, info, args),
@@ -29,6 +32,16 @@ pub fn eval(
     }
 }
 
+pub fn eval_const(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &IntConstConcreteLibfunc<Sint128Traits>,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [] = args.try_into().unwrap();
+
+    EvalAction::NormalBranch(0, smallvec![Value::I128(info.c)])
+}
+
 fn eval_diff(
     _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
     _selector: &SignatureOnlyConcreteLibfunc,
@@ -135,7 +148,21 @@ pub fn eval_from_felt(
 
 #[cfg(test)]
 mod tests {
-    use crate::{load_cairo, test_utils::run_test_program, Value};
+    use crate::{
+        load_cairo,
+        test_utils::{run_libfunc, run_test_program},
+        Value,
+    };
+
+    #[test]
+    fn test_eval_const() {
+        for value in [i128::MIN, -1, 0, 1, i128::MAX] {
+            assert_eq!(
+                run_libfunc("type i128 = i128;", &format!("i128_const<{value}>"), vec![]),
+                (0, vec![Value::I128(value)])
+            );
+        }
+    }
 
     #[test]
     fn test_eval_diff() {
@@ -0,0 +1,191 @@
+use super::EvalAction;
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType},
+        lib_func::SignatureOnlyConcreteLibfunc,
+        range::IntRangeConcreteLibfunc,
+    },
+    program_registry::ProgramRegistry,
+};
+use num_bigint::BigInt;
+use smallvec::smallvec;
+
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &IntRangeConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    match selector {
+        IntRangeConcreteLibfunc::TryNew(info) => eval_try_new(registry, info, args),
+        IntRangeConcreteLibfunc::PopFront(info) => eval_pop_front(registry, info, args),
+    }
+}
+
+pub fn eval_try_new(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [range_check @ Value::Unit, x, y]: [Value; 3] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    // An invalid range is returned as the empty range `[y, y)`.
+    if to_bigint(&x) <= to_bigint(&y) {
+        EvalAction::NormalBranch(
+            0,
+            smallvec![
+                range_check,
+                Value::IntRange {
+                    x: Box::new(x),
+                    y: Box::new(y),
+                },
+            ],
+        )
+    } else {
+        EvalAction::NormalBranch(
+            1,
+            smallvec![
+                range_check,
+                Value::IntRange {
+                    x: Box::new(y.clone()),
+                    y: Box::new(y),
+                },
+            ],
+        )
+    }
+}
+
+pub fn eval_pop_front(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::IntRange { x, y }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    let x_value = to_bigint(&x);
+    if x_value < to_bigint(&y) {
+        let next = with_value(&x, x_value + 1);
+        EvalAction::NormalBranch(
+            1,
+            smallvec![
+                Value::IntRange {
+                    x: Box::new(next),
+                    y,
+                },
+                *x,
+            ],
+        )
+    } else {
+        EvalAction::NormalBranch(0, smallvec![])
+    }
+}
+
+fn to_bigint(value: &Value) -> BigInt {
+    match value {
+        Value::BoundedInt { value, .. } => value.clone(),
+        Value::I8(value) => BigInt::from(*value),
+        Value::I16(value) => BigInt::from(*value),
+        Value::I32(value) => BigInt::from(*value),
+        Value::I64(value) => BigInt::from(*value),
+        Value::I128(value) => BigInt::from(*value),
+        Value::U8(value) => BigInt::from(*value),
+        Value::U16(value) => BigInt::from(*value),
+        Value::U32(value) => BigInt::from(*value),
+        Value::U64(value) => BigInt::from(*value),
+        Value::U128(value) => BigInt::from(*value),
+        _ => panic!("not an integer value"),
+    }
+}
+
+/// Build a value of the same integer type as `ty`. Never overflows, since the value is within a
+/// range of that type.
+fn with_value(ty: &Value, value: BigInt) -> Value {
+    match ty {
+        Value::BoundedInt { range, .. } => Value::BoundedInt {
+            range: range.clone(),
+            value,
+        },
+        Value::I8(_) => Value::I8(value.try_into().unwrap()),
+        Value::I16(_) => Value::I16(value.try_into().unwrap()),
+        Value::I32(_) => Value::I32(value.try_into().unwrap()),
+        Value::I64(_) => Value::I64(value.try_into().unwrap()),
+        Value::I128(_) => Value::I128(value.try_into().unwrap()),
+        Value::U8(_) => Value::U8(value.try_into().unwrap()),
+        Value::U16(_) => Value::U16(value.try_into().unwrap()),
+        Value::U32(_) => Value::U32(value.try_into().unwrap()),
+        Value::U64(_) => Value::U64(value.try_into().unwrap()),
+        Value::U128(_) => Value::U128(value.try_into().unwrap()),
+        _ => panic!("not an integer value"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_test_vm, Value};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_int_range() {
+        // Collect up to three integers of `[x, y)` into an array.
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type u32 = u32;
+                    type Array<u32> = Array<u32>;
+                    type IntRange<u32> = IntRange<u32>;
+
+                    libfunc int_range_try_new<u32> = int_range_try_new<u32>;
+                    libfunc int_range_pop_front<u32> = int_range_pop_front<u32>;
+                    libfunc array_new<u32> = array_new<u32>;
+                    libfunc array_append<u32> = array_append<u32>;
+                    libfunc branch_align = branch_align;
+                    libfunc drop<IntRange<u32>> = drop<IntRange<u32>>;
+
+                    array_new<u32>() -> ([3]);
+                    int_range_try_new<u32>([0], [1], [2]) { fallthrough([0], [4]) 20([0], [4]) };
+                    branch_align() -> ();
+                    int_range_pop_front<u32>([4]) { fallthrough() 6([4], [5]) };
+                    branch_align() -> ();
+                    return([0], [3]);
+                    branch_align() -> ();
+                    array_append<u32>([3], [5]) -> ([3]);
+                    int_range_pop_front<u32>([4]) { fallthrough() 11([4], [5]) };
+                    branch_align() -> ();
+                    return([0], [3]);
+                    branch_align() -> ();
+                    array_append<u32>([3], [5]) -> ([3]);
+                    int_range_pop_front<u32>([4]) { fallthrough() 16([4], [5]) };
+                    branch_align() -> ();
+                    return([0], [3]);
+                    branch_align() -> ();
+                    array_append<u32>([3], [5]) -> ([3]);
+                    drop<IntRange<u32>>([4]) -> ();
+                    return([0], [3]);
+                    branch_align() -> ();
+                    drop<IntRange<u32>>([4]) -> ();
+                    return([0], [3]);
+
+                    main@0([0]: RangeCheck, [1]: u32, [2]: u32) -> (RangeCheck, Array<u32>);
+                "#,
+            )
+            .unwrap();
+
+        let run = |x: u32, y: u32| {
+            let (_, values) = run_test_vm(program.clone(), 0, vec![Value::U32(x), Value::U32(y)]);
+            match values.last() {
+                Some(Value::Array { data, .. }) => data.clone(),
+                _ => panic!(),
+            }
+        };
+
+        assert_eq!(run(3, 5), [Value::U32(3), Value::U32(4)]);
+        assert_eq!(run(1, 9), [Value::U32(1), Value::U32(2), Value::U32(3)]);
+        assert_eq!(run(5, 5), []);
+        assert_eq!(run(7, 3), []);
+    }
+}
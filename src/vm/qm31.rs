@@ -0,0 +1,308 @@
+use super::EvalAction;
+use crate::Value;
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        lib_func::SignatureOnlyConcreteLibfunc,
+        qm31::{
+            QM31BinaryOpConcreteLibfunc, QM31BinaryOperator, QM31Concrete, QM31ConstConcreteLibfunc,
+        },
+        ConcreteLibfunc,
+    },
+    ids::ConcreteTypeId,
+    program_registry::ProgramRegistry,
+};
+use num_bigint::BigInt;
+use smallvec::smallvec;
+
+/// The modulus of the M31 field.
+const P: u32 = (1 << 31) - 1;
+
+pub fn eval(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    selector: &QM31Concrete,
+    args: Vec<Value>,
+) -> EvalAction {
+    match selector {
+        QM31Concrete::BinaryOperation(info) => eval_binary_operation(registry, info, args),
+        QM31Concrete::Const(info) => eval_const(registry, info, args),
+        QM31Concrete::IsZero(info) => eval_is_zero(registry, info, args),
+        QM31Concrete::Pack(info) => eval_pack(registry, info, args),
+        QM31Concrete::Unpack(info) => eval_unpack(registry, info, args),
+        QM31Concrete::FromM31(info) => eval_from_m31(registry, info, args),
+    }
+}
+
+/// Both the `qm31` and the `m31` operations, since an `m31` is a `qm31` with only its first
+/// coordinate.
+pub fn eval_binary_operation(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &QM31BinaryOpConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let apply = |lhs: [u32; 4], rhs: [u32; 4]| match info.operator {
+        QM31BinaryOperator::Add => qm31_add(lhs, rhs),
+        QM31BinaryOperator::Sub => qm31_sub(lhs, rhs),
+        QM31BinaryOperator::Mul => qm31_mul(lhs, rhs),
+        QM31BinaryOperator::Div => qm31_mul(lhs, qm31_inv(rhs)),
+    };
+
+    let result = match <[Value; 2]>::try_from(args).unwrap() {
+        [Value::QM31(lhs), Value::QM31(rhs)] => Value::QM31(apply(lhs, rhs)),
+        [Value::BoundedInt { range, value: lhs }, Value::BoundedInt { value: rhs, .. }] => {
+            let [value, ..] = apply([to_m31(&lhs), 0, 0, 0], [to_m31(&rhs), 0, 0, 0]);
+            Value::BoundedInt {
+                range,
+                value: value.into(),
+            }
+        }
+        _ => panic!(),
+    };
+
+    EvalAction::NormalBranch(0, smallvec![result])
+}
+
+pub fn eval_const(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &QM31ConstConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [] = args.try_into().unwrap();
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![Value::QM31([info.w0, info.w1, info.w2, info.w3])],
+    )
+}
+
+pub fn eval_is_zero(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [vm_value @ Value::QM31(value)]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    if value == [0; 4] {
+        EvalAction::NormalBranch(0, smallvec![])
+    } else {
+        EvalAction::NormalBranch(1, smallvec![vm_value])
+    }
+}
+
+pub fn eval_pack(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let coordinates: [Value; 4] = args.try_into().unwrap();
+    let coordinates = coordinates.map(|value| match value {
+        Value::BoundedInt { value, .. } => to_m31(&value),
+        _ => panic!(),
+    });
+
+    EvalAction::NormalBranch(0, smallvec![Value::QM31(coordinates)])
+}
+
+pub fn eval_unpack(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [range_check @ Value::Unit, Value::QM31(coordinates)]: [Value; 2] =
+        args.try_into().unwrap()
+    else {
+        panic!()
+    };
+
+    let m31 = |value: u32| m31_value(registry, &info.branch_signatures()[0].vars[1].ty, value);
+    let [w0, w1, w2, w3] = coordinates;
+
+    EvalAction::NormalBranch(
+        0,
+        smallvec![range_check, m31(w0), m31(w1), m31(w2), m31(w3)],
+    )
+}
+
+pub fn eval_from_m31(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    _info: &SignatureOnlyConcreteLibfunc,
+    args: Vec<Value>,
+) -> EvalAction {
+    let [Value::BoundedInt { value, .. }]: [Value; 1] = args.try_into().unwrap() else {
+        panic!()
+    };
+
+    EvalAction::NormalBranch(0, smallvec![Value::QM31([to_m31(&value), 0, 0, 0])])
+}
+
+fn to_m31(value: &BigInt) -> u32 {
+    let value = u32::try_from(value).unwrap();
+    assert!(value < P);
+    value
+}
+
+fn m31_value(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    ty: &ConcreteTypeId,
+    value: u32,
+) -> Value {
+    let CoreTypeConcrete::BoundedInt(info) = registry.get_type(ty).unwrap() else {
+        panic!()
+    };
+
+    Value::BoundedInt {
+        range: info.range.lower.clone()..info.range.upper.clone(),
+        value: value.into(),
+    }
+}
+
+fn m31_add(lhs: u32, rhs: u32) -> u32 {
+    ((lhs as u64 + rhs as u64) % P as u64) as u32
+}
+
+fn m31_sub(lhs: u32, rhs: u32) -> u32 {
+    m31_add(lhs, P - rhs)
+}
+
+fn m31_mul(lhs: u32, rhs: u32) -> u32 {
+    ((lhs as u64 * rhs as u64) % P as u64) as u32
+}
+
+fn m31_inv(value: u32) -> u32 {
+    assert_ne!(value, 0, "division by zero");
+
+    // Fermat's little theorem: value^(P - 2).
+    let (mut result, mut base, mut exp) = (1, value, P - 2);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = m31_mul(result, base);
+        }
+        base = m31_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+// CM31 is M31[i] / (i^2 + 1), stored as `[real, imaginary]`.
+
+fn cm31_add([a, b]: [u32; 2], [c, d]: [u32; 2]) -> [u32; 2] {
+    [m31_add(a, c), m31_add(b, d)]
+}
+
+fn cm31_sub([a, b]: [u32; 2], [c, d]: [u32; 2]) -> [u32; 2] {
+    [m31_sub(a, c), m31_sub(b, d)]
+}
+
+fn cm31_mul([a, b]: [u32; 2], [c, d]: [u32; 2]) -> [u32; 2] {
+    [
+        m31_sub(m31_mul(a, c), m31_mul(b, d)),
+        m31_add(m31_mul(a, d), m31_mul(b, c)),
+    ]
+}
+
+fn cm31_inv([a, b]: [u32; 2]) -> [u32; 2] {
+    let norm_inv = m31_inv(m31_add(m31_mul(a, a), m31_mul(b, b)));
+    [m31_mul(a, norm_inv), m31_mul(m31_sub(0, b), norm_inv)]
+}
+
+// QM31 is CM31[u] / (u^2 - 2 - i), stored as `[a.real, a.imaginary, b.real, b.imaginary]` for
+// `a + b * u`.
+
+/// The square of `u`.
+const R: [u32; 2] = [2, 1];
+
+fn split([w0, w1, w2, w3]: [u32; 4]) -> ([u32; 2], [u32; 2]) {
+    ([w0, w1], [w2, w3])
+}
+
+fn join([w0, w1]: [u32; 2], [w2, w3]: [u32; 2]) -> [u32; 4] {
+    [w0, w1, w2, w3]
+}
+
+fn qm31_add(lhs: [u32; 4], rhs: [u32; 4]) -> [u32; 4] {
+    let ((a, b), (c, d)) = (split(lhs), split(rhs));
+    join(cm31_add(a, c), cm31_add(b, d))
+}
+
+fn qm31_sub(lhs: [u32; 4], rhs: [u32; 4]) -> [u32; 4] {
+    let ((a, b), (c, d)) = (split(lhs), split(rhs));
+    join(cm31_sub(a, c), cm31_sub(b, d))
+}
+
+fn qm31_mul(lhs: [u32; 4], rhs: [u32; 4]) -> [u32; 4] {
+    // (a + bu) * (c + du) = ac + R * bd + (ad + bc) * u
+    let ((a, b), (c, d)) = (split(lhs), split(rhs));
+    join(
+        cm31_add(cm31_mul(a, c), cm31_mul(R, cm31_mul(b, d))),
+        cm31_add(cm31_mul(a, d), cm31_mul(b, c)),
+    )
+}
+
+fn qm31_inv(value: [u32; 4]) -> [u32; 4] {
+    // (a + bu)^-1 = (a - bu) / (a^2 - R * b^2)
+    let (a, b) = split(value);
+    let denominator_inv = cm31_inv(cm31_sub(cm31_mul(a, a), cm31_mul(R, cm31_mul(b, b))));
+    join(
+        cm31_mul(a, denominator_inv),
+        cm31_mul(cm31_sub([0, 0], b), denominator_inv),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::run_test_program;
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_qm31_arithmetic() {
+        let lhs = [1, 2, 3, 4];
+        let rhs = [5, 6, 7, 8];
+
+        assert_eq!(qm31_add(lhs, rhs), [6, 8, 10, 12]);
+        assert_eq!(qm31_sub(lhs, rhs), [P - 4, P - 4, P - 4, P - 4]);
+        // (1 + 2i + (3 + 4i)u) * (5 + 6i + (7 + 8i)u), with u^2 = 2 + i.
+        assert_eq!(qm31_mul(lhs, rhs), [P - 81, 109, P - 18, 60]);
+        assert_eq!(qm31_mul(qm31_inv(rhs), rhs), [1, 0, 0, 0]);
+        assert_eq!(qm31_mul(qm31_mul(lhs, qm31_inv(rhs)), rhs), lhs);
+        assert_eq!(m31_mul(m31_inv(P - 1), P - 1), 1);
+    }
+
+    #[test]
+    fn test_qm31_libfuncs() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type RangeCheck = RangeCheck;
+                    type qm31 = qm31;
+                    type m31 = BoundedInt<0, 2147483646>;
+
+                    libfunc qm31_const<1, 2, 3, 4> = qm31_const<1, 2, 3, 4>;
+                    libfunc qm31_const<5, 6, 7, 8> = qm31_const<5, 6, 7, 8>;
+                    libfunc qm31_mul = qm31_mul;
+                    libfunc qm31_unpack = qm31_unpack;
+                    libfunc m31_add = m31_add;
+                    libfunc drop<m31> = drop<m31>;
+
+                    qm31_const<1, 2, 3, 4>() -> ([1]);
+                    qm31_const<5, 6, 7, 8>() -> ([2]);
+                    qm31_mul([1], [2]) -> ([3]);
+                    qm31_unpack([0], [3]) -> ([0], [4], [5], [6], [7]);
+                    m31_add([4], [5]) -> ([8]);
+                    drop<m31>([6]) -> ();
+                    drop<m31>([7]) -> ();
+                    return([0], [8]);
+
+                    main@0([0]: RangeCheck) -> (RangeCheck, m31);
+                "#,
+            )
+            .unwrap();
+
+        let Some(Value::BoundedInt { value, .. }) = run_test_program(program).pop() else {
+            panic!()
+        };
+        assert_eq!(value, BigInt::from(109 - 81));
+    }
+}
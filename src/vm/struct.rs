@@ -77,15 +77,15 @@ pub fn eval_snapshot_deconstruct(
         panic!()
     };
 
-    let CoreTypeConcrete::Snapshot(snapshot_ty) = registry
-        .get_type(&info.signature.param_signatures[0].ty)
-        .unwrap()
-    else {
-        panic!()
+    // The snapshot of a duplicatable struct is the struct itself.
+    let param_ty = &info.signature.param_signatures[0].ty;
+    let struct_ty = match registry.get_type(param_ty).unwrap() {
+        CoreTypeConcrete::Snapshot(snapshot_ty) => &snapshot_ty.ty,
+        _ => param_ty,
     };
 
     let CoreTypeConcrete::Struct(StructConcreteType { members, .. }) =
-        registry.get_type(&snapshot_ty.ty).unwrap()
+        registry.get_type(struct_ty).unwrap()
     else {
         panic!()
     };
@@ -0,0 +1,25 @@
+use super::EvalAction;
+use crate::{panic::format_felt, Value};
+use cairo_lang_sierra::{
+    extensions::{
+        consts::SignatureAndConstConcreteLibfunc,
+        core::{CoreLibfunc, CoreType},
+    },
+    program_registry::ProgramRegistry,
+};
+use smallvec::smallvec;
+use starknet_types_core::felt::Felt;
+
+pub fn eval(
+    _registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    info: &SignatureAndConstConcreteLibfunc,
+    args: Vec<Value>,
+    output: &mut Vec<String>,
+) -> EvalAction {
+    let [] = args.try_into().unwrap();
+
+    let flag = Felt::from(&info.c);
+    output.push(format!("[TRACE]\t{}\n", format_felt(&flag)));
+
+    EvalAction::NormalBranch(0, smallvec![])
+}
@@ -59,16 +59,21 @@ pub fn eval_square_root(
     _info: &SignatureOnlyConcreteLibfunc,
     args: Vec<Value>,
 ) -> EvalAction {
-    let [range_check @ Value::Unit, Value::U128(value)]: [Value; 2] = args.try_into().unwrap()
-    else {
+    let [range_check @ Value::Unit, value]: [Value; 2] = args.try_into().unwrap() else {
         panic!()
     };
 
-    let value_big = BigUint::from(value);
-
-    let result: u64 = value_big.sqrt().try_into().unwrap();
+    // The root of an unsigned integer always fits in half its bits.
+    let result = match value {
+        Value::U8(value) => Value::U8(value.isqrt()),
+        Value::U16(value) => Value::U8(value.isqrt().try_into().unwrap()),
+        Value::U32(value) => Value::U16(value.isqrt().try_into().unwrap()),
+        Value::U64(value) => Value::U32(value.isqrt().try_into().unwrap()),
+        Value::U128(value) => Value::U64(value.isqrt().try_into().unwrap()),
+        _ => panic!(),
+    };
 
-    EvalAction::NormalBranch(0, smallvec![range_check, Value::U64(result)])
+    EvalAction::NormalBranch(0, smallvec![range_check, result])
 }
 
 pub fn eval_guarantee_verify(
@@ -235,7 +240,64 @@ pub fn eval_byte_reverse(
 
 #[cfg(test)]
 mod test {
-    use crate::{load_cairo, test_utils::run_test_program, Value};
+    use crate::{
+        load_cairo,
+        test_utils::{run_libfunc, run_test_program},
+        Value,
+    };
+
+    const TYPES: &str = r#"
+        type RangeCheck = RangeCheck;
+        type u8 = u8;
+        type u16 = u16;
+        type u32 = u32;
+        type u64 = u64;
+        type u128 = u128;
+    "#;
+
+    /// Every value of the narrow types, and the squares around the bounds of the wider ones.
+    fn square_root_inputs(max: u128) -> Vec<u128> {
+        if max <= u8::MAX.into() {
+            return (0..=max).collect();
+        }
+
+        [0, 1, 2, 3, 4, max.isqrt() - 1, max.isqrt()]
+            .into_iter()
+            .flat_map(|root| [root * root, root * root + 1, root * root + 2 * root])
+            .chain([max - 1, max])
+            .collect()
+    }
+
+    fn uint_value(ty: &str, value: u128) -> Value {
+        match ty {
+            "u8" => Value::U8(value.try_into().unwrap()),
+            "u16" => Value::U16(value.try_into().unwrap()),
+            "u32" => Value::U32(value.try_into().unwrap()),
+            "u64" => Value::U64(value.try_into().unwrap()),
+            _ => Value::U128(value),
+        }
+    }
+
+    #[test]
+    fn test_square_root_every_width() {
+        let widths = [
+            ("u8", "u8", u8::MAX.into()),
+            ("u16", "u8", u16::MAX.into()),
+            ("u32", "u16", u32::MAX.into()),
+            ("u64", "u32", u64::MAX.into()),
+            ("u128", "u64", u128::MAX),
+        ];
+
+        for (ty, root_ty, max) in widths {
+            for x in square_root_inputs(max) {
+                assert_eq!(
+                    run_libfunc(TYPES, &format!("{ty}_sqrt"), vec![uint_value(ty, x)]),
+                    (0, vec![Value::Unit, uint_value(root_ty, x.isqrt())]),
+                    "{ty}_sqrt({x})"
+                );
+            }
+        }
+    }
 
     #[test]
     fn test_square_root() {
@@ -1,4 +1,4 @@
-use super::EvalAction;
+use super::{uint128, EvalAction};
 use crate::Value;
 use cairo_lang_sierra::{
     extensions::{
@@ -22,7 +22,7 @@ pub fn eval(
     match selector {
         Uint16Concrete::Const(info) => eval_const(registry, info, args),
         Uint16Concrete::Operation(info) => eval_operation(registry, info, args),
-        Uint16Concrete::SquareRoot(_) => todo!(),
+        Uint16Concrete::SquareRoot(info) => uint128::eval_square_root(registry, info, args),
         Uint16Concrete::Equal(info) => eval_equal(registry, info, args),
         Uint16Concrete::ToFelt252(info) => eval_to_felt252(registry, info, args),
         Uint16Concrete::FromFelt252(info) => eval_from_felt(registry, info, args),
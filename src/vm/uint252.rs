@@ -104,7 +104,9 @@ pub fn u516_to_value(value: BigUint) -> Value {
     let upper_u256: BigUint = &value >> 256u32;
     let hi1: u128 = (&upper_u256 >> 128u32).try_into().unwrap();
     let lo1: u128 = (upper_u256 & BigUint::from(u128::MAX)).try_into().unwrap();
-    let hi: u128 = (&value >> 128u32).try_into().unwrap();
+    let hi: u128 = ((&value >> 128u32) & BigUint::from(u128::MAX))
+        .try_into()
+        .unwrap();
     let lo: u128 = (value & BigUint::from(u128::MAX)).try_into().unwrap();
     Value::Struct(vec![
         Value::U128(lo),
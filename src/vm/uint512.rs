@@ -66,3 +66,30 @@ pub fn eval_divmod(
         ],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::run_libfunc, Value};
+
+    #[test]
+    fn test_divmod_keeps_every_limb() {
+        let limbs =
+            |values: &[u128]| Value::Struct(values.iter().copied().map(Value::U128).collect());
+
+        let (branch, results) = run_libfunc(
+            r#"
+                type RangeCheck = RangeCheck;
+                type u128 = u128;
+                type U128MulGuarantee = U128MulGuarantee;
+                type u256 = Struct<ut@core::integer::u256, u128, u128>;
+                type u512 = Struct<ut@core::integer::u512, u128, u128, u128, u128>;
+                type NonZero<u256> = NonZero<u256>;
+            "#,
+            "u512_safe_divmod_by_u256",
+            vec![limbs(&[1, 2, 3, 4]), limbs(&[1, 0])],
+        );
+
+        assert_eq!(branch, 0);
+        assert_eq!(results[1..3], [limbs(&[1, 2, 3, 4]), limbs(&[0, 0])]);
+    }
+}
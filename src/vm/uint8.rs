@@ -1,4 +1,4 @@
-use super::EvalAction;
+use super::{uint128, EvalAction};
 use crate::Value;
 use cairo_lang_sierra::{
     extensions::{
@@ -22,7 +22,7 @@ pub fn eval(
     match selector {
         Uint8Concrete::Const(info) => eval_const(registry, info, args),
         Uint8Concrete::Operation(info) => eval_operation(registry, info, args),
-        Uint8Concrete::SquareRoot(_) => todo!(),
+        Uint8Concrete::SquareRoot(info) => uint128::eval_square_root(registry, info, args),
         Uint8Concrete::Equal(info) => eval_equal(registry, info, args),
         Uint8Concrete::ToFelt252(info) => eval_to_felt252(registry, info, args),
         Uint8Concrete::FromFelt252(info) => eval_from_felt(registry, info, args),
@@ -0,0 +1,174 @@
+use crate::Value;
+use cairo_lang_sierra::{ids::ConcreteTypeId, program::StatementIdx};
+use starknet_types_core::felt::Felt;
+use std::{
+    fmt::{self, Debug},
+    sync::Arc,
+};
+
+/// A custom condition on a produced value and its type.
+pub type WatchPredicate = Arc<dyn Fn(&ConcreteTypeId, &Value) -> bool + Send + Sync>;
+
+/// A condition on the values produced by libfuncs, checked by the
+/// [`VirtualMachine`](crate::VirtualMachine) after every statement.
+#[derive(Clone, Debug)]
+pub struct Watchpoint {
+    /// Only check the values produced by this statement, making it a conditional breakpoint.
+    pub statement_idx: Option<StatementIdx>,
+    pub condition: WatchCondition,
+}
+
+#[derive(Clone)]
+pub enum WatchCondition {
+    /// A produced value equals this one.
+    Value(Value),
+    /// A value of this type is produced.
+    Type(ConcreteTypeId),
+    /// A key that wasn't in a dict is accessed, which inserts it.
+    DictKeyInserted(Felt),
+    /// An array of at least this length is produced.
+    ArrayLenAtLeast(usize),
+    /// A custom condition on the produced values and their types.
+    Predicate(WatchPredicate),
+}
+
+/// A watchpoint whose condition has been met.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+    /// Index of the watchpoint in the VM.
+    pub index: usize,
+    /// The statement that produced the value.
+    pub statement_idx: StatementIdx,
+    pub value: Value,
+}
+
+impl Watchpoint {
+    pub fn new(condition: WatchCondition) -> Self {
+        Self {
+            statement_idx: None,
+            condition,
+        }
+    }
+
+    pub fn at(statement_idx: StatementIdx, condition: WatchCondition) -> Self {
+        Self {
+            statement_idx: Some(statement_idx),
+            condition,
+        }
+    }
+
+    /// The key of a [`WatchCondition::DictKeyInserted`], which needs the arguments to be checked.
+    pub(crate) fn dict_key(&self) -> Option<&Felt> {
+        match &self.condition {
+            WatchCondition::DictKeyInserted(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Find the first result of a statement that meets the condition. `key_existed` tells whether
+    /// the key of a [`WatchCondition::DictKeyInserted`] was in the dict before the statement.
+    pub(crate) fn check<'a>(
+        &self,
+        statement_idx: StatementIdx,
+        results: impl IntoIterator<Item = (&'a ConcreteTypeId, &'a Value)>,
+        key_existed: bool,
+    ) -> Option<&'a Value> {
+        if self
+            .statement_idx
+            .is_some_and(|watched| watched != statement_idx)
+        {
+            return None;
+        }
+
+        results
+            .into_iter()
+            .find(|(ty, value)| match &self.condition {
+                WatchCondition::Value(expected) => *value == expected,
+                WatchCondition::Type(expected) => *ty == expected,
+                WatchCondition::DictKeyInserted(expected) => {
+                    !key_existed
+                        && matches!(value, Value::FeltDictEntry { key, .. } if key == expected)
+                }
+                WatchCondition::ArrayLenAtLeast(len) => {
+                    matches!(value, Value::Array { data, .. } if data.len() >= *len)
+                }
+                WatchCondition::Predicate(predicate) => predicate(ty, value),
+            })
+            .map(|(_, value)| value)
+    }
+}
+
+impl Debug for WatchCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => f.debug_tuple("Value").field(value).finish(),
+            Self::Type(ty) => f.debug_tuple("Type").field(ty).finish(),
+            Self::DictKeyInserted(key) => f.debug_tuple("DictKeyInserted").field(key).finish(),
+            Self::ArrayLenAtLeast(len) => f.debug_tuple("ArrayLenAtLeast").field(len).finish(),
+            Self::Predicate(_) => f.write_str("Predicate(..)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{starknet::StubSyscallHandler, VirtualMachine};
+    use cairo_lang_sierra::ProgramParser;
+
+    #[test]
+    fn test_watch_values() {
+        let program = ProgramParser::new()
+            .parse(
+                r#"
+                    type felt252 = felt252;
+
+                    libfunc felt252_add = felt252_add;
+                    libfunc dup<felt252> = dup<felt252>;
+                    libfunc store_temp<felt252> = store_temp<felt252>;
+
+                    dup<felt252>([0]) -> ([0], [1]);
+                    felt252_add([0], [1]) -> ([2]);
+                    dup<felt252>([2]) -> ([2], [3]);
+                    felt252_add([2], [3]) -> ([4]);
+                    store_temp<felt252>([4]) -> ([4]);
+                    return([4]);
+
+                    main@0([0]: felt252) -> (felt252);
+                "#,
+            )
+            .unwrap();
+        let program = Arc::new(program);
+        let syscall_handler = &mut StubSyscallHandler::default();
+
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.add_watchpoint(Watchpoint::at(
+            StatementIdx(4),
+            WatchCondition::Value(Value::Felt(84.into())),
+        ));
+        vm.add_watchpoint(Watchpoint::new(WatchCondition::Value(Value::Felt(
+            42.into(),
+        ))));
+        vm.call_program(&program.funcs[0], 0, [Value::Felt(21.into())]);
+
+        let hit = vm.run_until_watch(syscall_handler).unwrap();
+        assert_eq!(
+            hit,
+            WatchHit {
+                index: 1,
+                statement_idx: StatementIdx(1),
+                value: Value::Felt(42.into()),
+            }
+        );
+
+        // The same value is produced again by the `dup`.
+        let hit = vm.run_until_watch(syscall_handler).unwrap();
+        assert_eq!((hit.index, hit.statement_idx), (1, StatementIdx(2)));
+
+        // The value is produced at statement 3 but only watched at statement 4.
+        let hit = vm.run_until_watch(syscall_handler).unwrap();
+        assert_eq!((hit.index, hit.statement_idx), (0, StatementIdx(4)));
+
+        assert_eq!(vm.run_until_watch(syscall_handler), None);
+    }
+}
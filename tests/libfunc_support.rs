@@ -0,0 +1,466 @@
+use cairo_lang_sierra::{
+    extensions::{
+        core::{CoreLibfunc, CoreType, CoreTypeConcrete},
+        starknet::StarknetTypeConcrete,
+        ConcreteLibfunc, GenericLibfunc,
+    },
+    ids::{ConcreteLibfuncId, ConcreteTypeId, FunctionId, GenericLibfuncId, VarId},
+    program::{
+        BranchInfo, BranchTarget, ConcreteLibfuncLongId, Function, FunctionSignature, GenericArg,
+        Invocation, LibfuncDeclaration, Param, Program, Statement, StatementIdx,
+    },
+    program_registry::ProgramRegistry,
+    ProgramParser,
+};
+use sierra_emu::{
+    starknet::StubSyscallHandler,
+    support::{is_libfunc_supported, is_type_supported},
+    BuiltinCosts, Value, VirtualMachine,
+};
+use std::{
+    cell::RefCell,
+    fmt::Write,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{Arc, Once},
+};
+
+/// Types the generic libfuncs are specialized with, along with the types their signatures need.
+const TYPE_POOL: &str = r#"
+    type felt252 = felt252;
+    type u8 = u8;
+    type u16 = u16;
+    type u32 = u32;
+    type u64 = u64;
+    type u128 = u128;
+    type i8 = i8;
+    type i16 = i16;
+    type i32 = i32;
+    type i64 = i64;
+    type i128 = i128;
+    type bytes31 = bytes31;
+    type Unit = Struct<ut@Tuple>;
+    type bool = Enum<ut@core::bool, Unit, Unit>;
+    type Pair = Struct<ut@Tuple, felt252, felt252>;
+    type u256 = Struct<ut@core::integer::u256, u128, u128>;
+    type u512 = Struct<ut@core::integer::u512, u128, u128, u128, u128>;
+    type U32x8 = Struct<ut@Tuple, u32, u32, u32, u32, u32, u32, u32, u32>;
+    type U32x16 = Struct<ut@Tuple, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32, u32>;
+    type RangeCheck = RangeCheck;
+    type RangeCheck96 = RangeCheck96;
+    type GasBuiltin = GasBuiltin;
+    type BuiltinCosts = BuiltinCosts;
+    type Bitwise = Bitwise;
+    type Pedersen = Pedersen;
+    type Poseidon = Poseidon;
+    type EcOp = EcOp;
+    type SegmentArena = SegmentArena;
+    type System = System;
+    type U128MulGuarantee = U128MulGuarantee;
+    type EcPoint = EcPoint;
+    type EcState = EcState;
+    type qm31 = qm31;
+    type NonZero<felt252> = NonZero<felt252>;
+    type NonZero<EcPoint> = NonZero<EcPoint>;
+    type NonZero<u8> = NonZero<u8>;
+    type NonZero<u16> = NonZero<u16>;
+    type NonZero<u32> = NonZero<u32>;
+    type NonZero<u64> = NonZero<u64>;
+    type NonZero<u128> = NonZero<u128>;
+    type NonZero<u256> = NonZero<u256>;
+    type NonZero<qm31> = NonZero<qm31>;
+    type Box<felt252> = Box<felt252>;
+    type Box<Pair> = Box<Pair>;
+    type Box<U32x8> = Box<U32x8>;
+    type Box<U32x16> = Box<U32x16>;
+    type Array<felt252> = Array<felt252>;
+    type Snapshot<Array<felt252>> = Snapshot<Array<felt252>>;
+    type Span<felt252> = Struct<ut@core::array::Span::<core::felt252>, Snapshot<Array<felt252>>>;
+    type Nullable<felt252> = Nullable<felt252>;
+    type Uninitialized<felt252> = Uninitialized<felt252>;
+    type Felt252Dict<felt252> = Felt252Dict<felt252>;
+    type Felt252DictEntry<felt252> = Felt252DictEntry<felt252>;
+    type SquashedFelt252Dict<felt252> = SquashedFelt252Dict<felt252>;
+    type ContractAddress = ContractAddress;
+    type ClassHash = ClassHash;
+    type StorageBaseAddress = StorageBaseAddress;
+    type StorageAddress = StorageAddress;
+    type Secp256k1Point = Secp256k1Point;
+    type Secp256r1Point = Secp256r1Point;
+    type Option<Secp256k1Point> = Enum<ut@core::option::Option::<Secp256k1Point>, Secp256k1Point, Unit>;
+    type Option<Secp256r1Point> = Enum<ut@core::option::Option::<Secp256r1Point>, Secp256r1Point, Unit>;
+    type Sha256StateHandle = Sha256StateHandle;
+    type IntRange<u8> = IntRange<u8>;
+    type BoundedInt<0, 10> = BoundedInt<0, 10>;
+    type BoundedInt<0, 20> = BoundedInt<0, 20>;
+    type BoundedInt<-10, 10> = BoundedInt<-10, 10>;
+    type m31 = BoundedInt<0, 2147483646>;
+    type u96 = BoundedInt<0, 79228162514264337593543950335>;
+    type Const<felt252, 5> = Const<felt252, 5>;
+    type Const<u8, 5> = Const<u8, 5>;
+    type Span<u8> = Span<u8>;
+    type Coupon<user@f> = Coupon<user@f>;
+
+    return();
+
+    f@0() -> ();
+"#;
+
+/// Declares `generic_id<generic_args>` as `libfunc` in a copy of the pool.
+fn with_libfunc(
+    pool: &Program,
+    generic_id: &GenericLibfuncId,
+    generic_args: Vec<GenericArg>,
+) -> Program {
+    let mut program = pool.clone();
+    program.libfunc_declarations.push(LibfuncDeclaration {
+        id: ConcreteLibfuncId::from_string("libfunc"),
+        long_id: ConcreteLibfuncLongId {
+            generic_id: generic_id.clone(),
+            generic_args,
+        },
+    });
+    program
+}
+
+/// Every specialization of `generic_id` the registry accepts, among the candidate generic args of
+/// the first shape that has any, along with whether the emulator supports it.
+fn specializations(pool: &Program, generic_id: &GenericLibfuncId) -> Vec<(Vec<GenericArg>, bool)> {
+    let types = pool
+        .type_declarations
+        .iter()
+        .map(|declaration| GenericArg::Type(declaration.id.clone()))
+        .collect::<Vec<_>>();
+    let values = || (0..3).map(|value| GenericArg::Value(value.into()));
+
+    let shapes: [Vec<Vec<GenericArg>>; 6] = [
+        vec![vec![]],
+        vec![vec![GenericArg::UserFunc(FunctionId::from_string("f"))]],
+        types.iter().cloned().map(|ty| vec![ty]).collect(),
+        values().map(|value| vec![value]).collect(),
+        types
+            .iter()
+            .flat_map(|ty| values().map(move |value| vec![ty.clone(), value]))
+            .collect(),
+        types
+            .iter()
+            .flat_map(|lhs| types.iter().map(move |rhs| vec![lhs.clone(), rhs.clone()]))
+            .collect(),
+    ];
+
+    shapes
+        .into_iter()
+        .map(|candidates| {
+            candidates
+                .into_iter()
+                .filter_map(|generic_args| {
+                    let program = with_libfunc(pool, generic_id, generic_args.clone());
+                    let registry = ProgramRegistry::new(&program).ok()?;
+                    let libfunc = registry
+                        .get_libfunc(&ConcreteLibfuncId::from_string("libfunc"))
+                        .unwrap();
+                    Some((generic_args, is_libfunc_supported(&registry, libfunc)))
+                })
+                .collect::<Vec<_>>()
+        })
+        .find(|specializations| !specializations.is_empty())
+        .unwrap_or_default()
+}
+
+/// Every libfunc of the compiler, and whether the emulator supports its specializations. Libfuncs
+/// that couldn't be specialized with the type pool are listed as untested, so that new ones still
+/// show up.
+#[test]
+fn libfunc_support_matrix() {
+    let pool = ProgramParser::new().parse(TYPE_POOL).unwrap();
+    ProgramRegistry::<CoreType, CoreLibfunc>::new(&pool).unwrap();
+
+    let mut generic_ids = CoreLibfunc::supported_ids();
+    generic_ids.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+
+    let mut matrix = String::new();
+    for generic_id in &generic_ids {
+        let specializations = specializations(&pool, generic_id);
+        let status = match (
+            specializations.iter().any(|(_, supported)| *supported),
+            specializations.iter().any(|(_, supported)| !*supported),
+        ) {
+            (true, false) => "supported",
+            (false, true) => "unsupported",
+            (true, true) => "partially supported",
+            (false, false) => "untested",
+        };
+        writeln!(matrix, "{generic_id} {status}").unwrap();
+    }
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/libfunc_support.txt");
+    if std::env::var_os("BLESS").is_some() {
+        fs::write(&path, &matrix).unwrap();
+    }
+    assert_eq!(
+        fs::read_to_string(&path).unwrap_or_default(),
+        matrix,
+        "the libfunc support matrix changed, rerun with BLESS=1 to update it"
+    );
+}
+
+/// Whether the VM fills in arguments of the type when calling a program, as builtins.
+fn is_filled_by_vm(ty: &CoreTypeConcrete) -> bool {
+    matches!(
+        ty,
+        CoreTypeConcrete::GasBuiltin(_)
+            | CoreTypeConcrete::RangeCheck(_)
+            | CoreTypeConcrete::RangeCheck96(_)
+            | CoreTypeConcrete::Bitwise(_)
+            | CoreTypeConcrete::Pedersen(_)
+            | CoreTypeConcrete::Poseidon(_)
+            | CoreTypeConcrete::SegmentArena(_)
+            | CoreTypeConcrete::Starknet(StarknetTypeConcrete::System(_))
+    )
+}
+
+/// An arbitrary value of the type, or `None` if there's no simple one to make up.
+fn sample_value(
+    registry: &ProgramRegistry<CoreType, CoreLibfunc>,
+    ty: &ConcreteTypeId,
+) -> Option<Value> {
+    Some(match registry.get_type(ty).unwrap() {
+        CoreTypeConcrete::Felt252(_)
+        | CoreTypeConcrete::Starknet(
+            StarknetTypeConcrete::ClassHash(_)
+            | StarknetTypeConcrete::ContractAddress(_)
+            | StarknetTypeConcrete::StorageBaseAddress(_)
+            | StarknetTypeConcrete::StorageAddress(_),
+        ) => Value::Felt(1.into()),
+        CoreTypeConcrete::Bytes31(_) => Value::Bytes31(1.into()),
+        CoreTypeConcrete::Uint8(_) => Value::U8(1),
+        CoreTypeConcrete::Uint16(_) => Value::U16(1),
+        CoreTypeConcrete::Uint32(_) => Value::U32(1),
+        CoreTypeConcrete::Uint64(_) => Value::U64(1),
+        CoreTypeConcrete::Uint128(_) => Value::U128(1),
+        CoreTypeConcrete::Sint8(_) => Value::I8(1),
+        CoreTypeConcrete::Sint16(_) => Value::I16(1),
+        CoreTypeConcrete::Sint32(_) => Value::I32(1),
+        CoreTypeConcrete::Sint64(_) => Value::I64(1),
+        CoreTypeConcrete::Sint128(_) => Value::I128(1),
+        CoreTypeConcrete::BoundedInt(info) => Value::BoundedInt {
+            range: info.range.lower.clone()..info.range.upper.clone(),
+            value: info.range.lower.clone().max(1.into()),
+        },
+        CoreTypeConcrete::QM31(_) => Value::QM31([1, 0, 0, 0]),
+        CoreTypeConcrete::EcOp(_) | CoreTypeConcrete::Uint128MulGuarantee(_) => Value::Unit,
+        CoreTypeConcrete::BuiltinCosts(_) => Value::BuiltinCosts(BuiltinCosts::default()),
+        CoreTypeConcrete::NonZero(info) | CoreTypeConcrete::Box(info) => {
+            return sample_value(registry, &info.ty)
+        }
+        CoreTypeConcrete::Snapshot(info) => return sample_value(registry, &info.ty),
+        CoreTypeConcrete::Array(info) => Value::Array {
+            ty: info.ty.clone(),
+            data: Vec::new(),
+        },
+        CoreTypeConcrete::Struct(info) => Value::Struct(
+            info.members
+                .iter()
+                .map(|member| sample_value(registry, member))
+                .collect::<Option<_>>()?,
+        ),
+        CoreTypeConcrete::Enum(info) => Value::Enum {
+            self_ty: ty.clone(),
+            index: 0,
+            payload: Box::new(sample_value(registry, info.variants.first()?)?),
+        },
+        _ => return None,
+    })
+}
+
+/// How running a libfunc went.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Ran,
+    /// It reached a `todo!()` or `unimplemented!()` of the emulator.
+    Unimplemented,
+    /// It panicked for another reason, most likely because of the made up arguments.
+    Panicked,
+}
+
+thread_local! {
+    static PANIC_FILE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Records the file of every panic, so that the ones in the stub syscall handler can be told apart
+/// from the ones in the emulator.
+fn record_panic_files() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let file = info.location().map(|location| location.file().to_string());
+            PANIC_FILE.with(|panic_file| *panic_file.borrow_mut() = file);
+            default_hook(info);
+        }));
+    });
+}
+
+/// Runs the specialization once, with the profilers enabled, in a program generated from its
+/// signature. Returns `None` if there are no sample values for its arguments.
+fn run_specialization(
+    pool: &Program,
+    generic_id: &GenericLibfuncId,
+    generic_args: Vec<GenericArg>,
+) -> Option<Outcome> {
+    let libfunc_id = ConcreteLibfuncId::from_string("libfunc");
+    let branch_align_id = ConcreteLibfuncId::from_string("branch_align");
+
+    let mut program = with_libfunc(pool, generic_id, generic_args);
+    program.libfunc_declarations.push(LibfuncDeclaration {
+        id: branch_align_id.clone(),
+        long_id: ConcreteLibfuncLongId {
+            generic_id: GenericLibfuncId::from_string("branch_align"),
+            generic_args: vec![],
+        },
+    });
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&program).unwrap();
+    let libfunc = registry.get_libfunc(&libfunc_id).unwrap();
+    let params = libfunc.param_signatures();
+
+    let args = params
+        .iter()
+        .filter(|param| !is_filled_by_vm(registry.get_type(&param.ty).unwrap()))
+        .map(|param| sample_value(&registry, &param.ty))
+        .collect::<Option<Vec<_>>>()?;
+
+    // Every branch jumps to its own `branch_align` and `return`, followed by the body of `f`.
+    program.statements = vec![Statement::Invocation(Invocation {
+        libfunc_id,
+        args: (0..params.len()).map(|id| VarId::new(id as u64)).collect(),
+        branches: libfunc
+            .branch_signatures()
+            .iter()
+            .enumerate()
+            .map(|(index, branch)| BranchInfo {
+                target: match libfunc.fallthrough() == Some(index) {
+                    true => BranchTarget::Fallthrough,
+                    false => BranchTarget::Statement(StatementIdx(1 + 2 * index)),
+                },
+                results: (params.len()..params.len() + branch.vars.len())
+                    .map(|id| VarId::new(id as u64))
+                    .collect(),
+            })
+            .collect(),
+    })];
+    for _ in libfunc.branch_signatures() {
+        program.statements.push(Statement::Invocation(Invocation {
+            libfunc_id: branch_align_id.clone(),
+            args: vec![],
+            branches: vec![BranchInfo {
+                target: BranchTarget::Fallthrough,
+                results: vec![],
+            }],
+        }));
+        program.statements.push(Statement::Return(vec![]));
+    }
+    program.statements.push(Statement::Return(vec![]));
+    program.funcs[0].entry_point = StatementIdx(program.statements.len() - 1);
+    program.funcs.push(Function {
+        id: FunctionId::from_string("main"),
+        signature: FunctionSignature {
+            param_types: params.iter().map(|param| param.ty.clone()).collect(),
+            ret_types: vec![],
+        },
+        params: params
+            .iter()
+            .enumerate()
+            .map(|(id, param)| Param {
+                id: VarId::new(id as u64),
+                ty: param.ty.clone(),
+            })
+            .collect(),
+        entry_point: StatementIdx(0),
+    });
+
+    let program = Arc::new(program);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut vm = VirtualMachine::new(program.clone());
+        vm.enable_profiler();
+        vm.enable_gas_profiler();
+        vm.call_program(program.funcs.last().unwrap(), 1000000, args);
+        vm.run_with_trace(&mut StubSyscallHandler::default());
+    }));
+
+    let Err(payload) = result else {
+        return Some(Outcome::Ran);
+    };
+    let message = payload
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| payload.downcast_ref::<&str>().copied())
+        .unwrap_or_default();
+    let is_stub = PANIC_FILE.with(|file| file.borrow().as_deref() == Some("src/starknet.rs"));
+
+    Some(
+        match message.starts_with("not yet implemented") || message.starts_with("not implemented") {
+            true if !is_stub => Outcome::Unimplemented,
+            _ => Outcome::Panicked,
+        },
+    )
+}
+
+/// Runs specializations of every libfunc, and checks that the supported ones never reach a
+/// `todo!()` of the emulator while the unsupported ones do.
+#[test]
+fn libfunc_support_matches_eval() {
+    record_panic_files();
+
+    let pool = ProgramParser::new().parse(TYPE_POOL).unwrap();
+
+    let mut mismatches = Vec::new();
+    for generic_id in CoreLibfunc::supported_ids() {
+        // A few specializations of each status are enough to tell, as long as one of them ran.
+        let mut checked = Vec::new();
+        for (generic_args, is_supported) in specializations(&pool, &generic_id) {
+            if checked.contains(&is_supported) {
+                continue;
+            }
+
+            let outcome = run_specialization(&pool, &generic_id, generic_args.clone());
+            let long_id = ConcreteLibfuncLongId {
+                generic_id: generic_id.clone(),
+                generic_args,
+            };
+            match (is_supported, outcome) {
+                (true, Some(Outcome::Unimplemented)) => {
+                    mismatches.push(format!("{long_id} is supported but isn't implemented"))
+                }
+                (false, Some(Outcome::Ran)) => {
+                    mismatches.push(format!("{long_id} is unsupported but ran"))
+                }
+                (_, Some(Outcome::Ran | Outcome::Unimplemented)) => {}
+                (_, Some(Outcome::Panicked) | None) => continue,
+            }
+            checked.push(is_supported);
+        }
+    }
+
+    assert!(mismatches.is_empty(), "{mismatches:#?}");
+}
+
+/// Checks `is_type_supported` against the types `Value::is` can check values against.
+#[test]
+fn type_support_matches_value_is() {
+    let pool = ProgramParser::new().parse(TYPE_POOL).unwrap();
+    let registry = ProgramRegistry::<CoreType, CoreLibfunc>::new(&pool).unwrap();
+
+    for declaration in &pool.type_declarations {
+        let is_checked = panic::catch_unwind(AssertUnwindSafe(|| {
+            Value::Unit.is(&registry, &declaration.id)
+        }))
+        .is_ok();
+        assert_eq!(
+            is_type_supported(registry.get_type(&declaration.id).unwrap()),
+            is_checked,
+            "{}",
+            declaration.id
+        );
+    }
+}